@@ -1,21 +1,146 @@
+use std::collections::HashMap;
+
 pub mod puzzle1;
 pub mod puzzle2;
 
-fn split_contents(contents: String) -> (Vec<i64>, Vec<i64>) {
+fn split_contents(contents: String) -> Result<(Vec<i64>, Vec<i64>), ListError> {
     let mut left: Vec<i64> = Vec::new();
     let mut right: Vec<i64> = Vec::new();
-    for line in contents.lines() {
-        let splits: Vec<&str> = line.split("   ").collect();
-        if splits.len() != 2 {
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
             continue;
         }
 
-        let value1: i64 = splits[0].parse().expect("value1 should be int");
-        let value2: i64 = splits[1].parse().expect("value2 should be int");
+        let malformed = || ListError::MalformedLine {
+            number: i + 1,
+            line: line.to_string(),
+        };
+        let values: Vec<i64> = line
+            .split_whitespace()
+            .map(|v| v.parse().map_err(|_| malformed()))
+            .collect::<Result<_, _>>()?;
+        let [value1, value2] = values[..] else {
+            return Err(malformed());
+        };
 
         left.push(value1);
         right.push(value2);
     }
 
-    (left, right)
+    Ok((left, right))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ListError {
+    // a line that isn't two numbers, numbered from 1
+    MalformedLine { number: usize, line: String },
+    LengthMismatch { left: usize, right: usize },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Pair {
+    pub left: i64,
+    pub right: i64,
+    pub distance: i64,
+}
+
+// the smallest left with the smallest right, then the next smallest of each and so on
+pub fn pair_lists(mut left: Vec<i64>, mut right: Vec<i64>) -> Result<Vec<Pair>, ListError> {
+    if left.len() != right.len() {
+        return Err(ListError::LengthMismatch {
+            left: left.len(),
+            right: right.len(),
+        });
+    }
+
+    left.sort();
+    right.sort();
+
+    Ok(left
+        .into_iter()
+        .zip(right)
+        .map(|(left, right)| Pair {
+            left,
+            right,
+            distance: (left - right).abs(),
+        })
+        .collect())
+}
+
+// each left value times how often it's in the right list, the lists don't need to be the
+// same length
+pub fn similarity(left: &[i64], right: &[i64]) -> i64 {
+    let mut counts: HashMap<i64, i64> = HashMap::new();
+    for value in right {
+        *counts.entry(*value).or_default() += 1;
+    }
+
+    left.iter()
+        .map(|value| value * counts.get(value).copied().unwrap_or_default())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs() {
+        let pairs = pair_lists(vec![3, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3]).unwrap();
+
+        assert_eq!(
+            Pair {
+                left: 1,
+                right: 3,
+                distance: 2
+            },
+            pairs[0]
+        );
+        assert_eq!(
+            vec![2, 1, 0, 1, 2, 5],
+            pairs.iter().map(|p| p.distance).collect::<Vec<i64>>()
+        );
+        assert_eq!(Ok(vec![]), pair_lists(vec![], vec![]));
+    }
+
+    #[test]
+    fn unequal_lengths() {
+        assert_eq!(
+            Err(ListError::LengthMismatch { left: 3, right: 2 }),
+            pair_lists(vec![1, 2, 3], vec![1, 2])
+        );
+
+        // only the distances need the lists to line up
+        assert_eq!(4, similarity(&[1, 2, 3], &[1, 3]));
+        assert_eq!(0, similarity(&[], &[1, 3]));
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert_eq!(
+            Ok((vec![3, 4], vec![4, 3])),
+            split_contents("3   4\n\n4   3\n".to_string())
+        );
+        assert_eq!(
+            Err(ListError::MalformedLine {
+                number: 2,
+                line: "4".to_string()
+            }),
+            split_contents("3   4\n4\n2   5".to_string())
+        );
+        assert_eq!(
+            Err(ListError::MalformedLine {
+                number: 1,
+                line: "3   4   5".to_string()
+            }),
+            split_contents("3   4   5".to_string())
+        );
+        assert_eq!(
+            Err(ListError::MalformedLine {
+                number: 1,
+                line: "3   x".to_string()
+            }),
+            split_contents("3   x".to_string())
+        );
+    }
 }
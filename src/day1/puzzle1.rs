@@ -1,37 +1,18 @@
 use crate::read_file;
 
-use super::split_contents;
+use super::{pair_lists, split_contents, ListError};
 
-pub fn solve_day1_puzzle1() -> i64 {
+pub fn solve_day1_puzzle1() -> Result<i64, ListError> {
     let contents = read_file("day1.txt");
-    let (left, right) = split_contents(contents);
+    let (left, right) = split_contents(contents)?;
 
     get_puzzle1_result(left, right)
 }
 
-fn get_puzzle1_result(mut left: Vec<i64>, mut right: Vec<i64>) -> i64 {
-    left.sort();
-    right.sort();
-
-    let mut right_index = 0;
-    let right_len = right.len();
-    let mut left_index = 0;
-    let left_len = left.len();
-    let mut distance: i64 = 0;
-
-    while left_index < left_len {
-        let left_value = left[left_index];
-        if right_index < right_len {
-            let right_value = right[right_index];
-            let diff = left_value - right_value;
-            distance += diff.abs();
-            right_index += 1;
-        } else {
-            break;
-        }
-        left_index += 1;
-    }
-    distance
+fn get_puzzle1_result(left: Vec<i64>, right: Vec<i64>) -> Result<i64, ListError> {
+    let pairs = pair_lists(left, right)?;
+
+    Ok(pairs.iter().map(|pair| pair.distance).sum())
 }
 
 #[cfg(test)]
@@ -45,7 +26,15 @@ mod tests {
 
         let result = get_puzzle1_result(left, right);
 
-        assert_eq!(11, result);
+        assert_eq!(Ok(11), result);
+    }
+
+    #[test]
+    fn unequal_lengths() {
+        assert_eq!(
+            Err(ListError::LengthMismatch { left: 3, right: 2 }),
+            get_puzzle1_result(vec![3, 4, 2], vec![4, 3])
+        );
     }
 
     #[test]
@@ -54,6 +43,6 @@ mod tests {
 
         let result = solve_day1_puzzle1();
 
-        assert_eq!(1341714, result);
+        assert_eq!(Ok(1341714), result);
     }
 }
@@ -1,30 +1,15 @@
 use crate::read_file;
 
-use super::split_contents;
+use super::{similarity, split_contents, ListError};
 
-pub fn solve_day1_puzzle2() -> i64 {
+pub fn solve_day1_puzzle2() -> Result<i64, ListError> {
     let contents = read_file("day1.txt");
-    let (left, right) = split_contents(contents);
-    get_puzzle2_result(left, right)
+    let (left, right) = split_contents(contents)?;
+    Ok(get_puzzle2_result(left, right))
 }
 
 fn get_puzzle2_result(left: Vec<i64>, right: Vec<i64>) -> i64 {
-    let mut similarity = 0_i64;
-
-    for left_value in left {
-        let mut found_count = 0_i64;
-        for right_value in right.iter() {
-            if left_value == *right_value {
-                found_count += 1;
-            }
-        }
-
-        if found_count > 0 {
-            similarity += left_value * found_count;
-        }
-    }
-
-    similarity
+    similarity(&left, &right)
 }
 
 #[cfg(test)]
@@ -47,6 +32,6 @@ mod tests {
 
         let result = solve_day1_puzzle2();
 
-        assert_eq!(27384707, result);
+        assert_eq!(Ok(27384707), result);
     }
 }
@@ -1,18 +1,61 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub mod puzzle1;
 pub mod puzzle2;
 
-type Grid = Vec<Vec<i64>>;
+// None is an impassable tile, shown as a . on the map
+type Grid = Vec<Vec<Option<i64>>>;
 
-struct Map {
+pub struct Map {
     grid: Grid,
+    rule: TrailRule,
+}
+
+// a trail goes from the start height to the end height, changing by exactly step with
+// every move
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrailRule {
+    pub step: i64,
+    pub diagonal: bool,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl TrailRule {
+    pub fn hiking() -> Self {
+        Self {
+            step: 1,
+            diagonal: false,
+            start: 0,
+            end: 9,
+        }
+    }
+
+    // how many steps along the trail a height is, if it can be on a trail at all
+    fn level(&self, height: i64) -> Option<i64> {
+        let climbed = height - self.start;
+        if self.step == 0 || climbed % self.step != 0 {
+            return None;
+        }
+
+        let level = climbed / self.step;
+        (0..=self.levels()?).contains(&level).then_some(level)
+    }
+
+    fn levels(&self) -> Option<i64> {
+        if self.step == 0 || (self.end - self.start) % self.step != 0 {
+            return None;
+        }
+
+        let levels = (self.end - self.start) / self.step;
+        (levels >= 0).then_some(levels)
+    }
 }
 
 #[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
-struct Pos {
-    x: i64,
-    y: i64,
+pub struct Pos {
+    pub x: i64,
+    pub y: i64,
 }
 
 enum Direction {
@@ -20,118 +63,185 @@ enum Direction {
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
 impl Map {
-    fn parse(content: &str) -> Map {
+    pub fn parse(content: &str) -> Map {
         let grid: Grid = content
             .lines()
             .map(|l| {
                 l.chars()
-                    .map(|c| c.to_digit(10).expect("should always be a digit") as i64)
+                    .map(|c| match c {
+                        '.' => None,
+                        c => Some(c.to_digit(10).expect("should always be a digit or a .") as i64),
+                    })
                     .collect()
             })
             .collect();
 
-        Map { grid }
+        Map {
+            grid,
+            rule: TrailRule::hiking(),
+        }
+    }
+
+    pub fn with_rule(self, rule: TrailRule) -> Map {
+        Map { rule, ..self }
     }
 
     fn get_map_total(&self) -> (i64, i64) {
+        let (summits, ratings) = self.count_trails();
+
         let mut score_sum = 0;
         let mut rating_sum = 0;
-        for (x, row) in self.grid.iter().enumerate() {
-            for (y, val) in row.iter().enumerate() {
-                if *val == 0 {
-                    let (sum, visited_nines) = self.get_trailhead_score(Pos {
-                        x: x as i64,
-                        y: y as i64,
-                    });
-                    score_sum += sum;
-                    rating_sum += visited_nines.values().sum::<i64>();
-                }
+        for pos in self.positions() {
+            if self.get_value(&pos) == Some(self.rule.start) {
+                score_sum += summits.get(&pos).map_or(0, |s| s.len() as i64);
+                rating_sum += ratings.get(&pos).copied().unwrap_or_default();
             }
         }
         (score_sum, rating_sum)
     }
 
-    fn get_value(&self, pos: &Pos) -> i64 {
-        if let Some(row) = self.grid.get(pos.x as usize) {
-            if let Some(value) = row.get(pos.y as usize) {
-                return *value;
-            }
-        }
-
-        -1
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.grid.iter().enumerate().flat_map(|(x, row)| {
+            (0..row.len()).map(move |y| Pos {
+                x: x as i64,
+                y: y as i64,
+            })
+        })
     }
 
-    fn get_trailhead_score(&self, starting_pos: Pos) -> (i64, HashMap<Pos, i64>) {
-        let mut visited_nines = HashMap::new();
-        self.recurse_path(&starting_pos, &mut visited_nines);
-        (visited_nines.len() as i64, visited_nines)
+    fn get_value(&self, pos: &Pos) -> Option<i64> {
+        if pos.x < 0 || pos.y < 0 {
+            return None;
+        }
+
+        self.grid
+            .get(pos.x as usize)
+            .and_then(|row| row.get(pos.y as usize))
+            .copied()
+            .flatten()
     }
 
-    fn recurse_path(&self, current_pos: &Pos, visited_nines: &mut HashMap<Pos, i64>) {
-        let current_value = self.get_value(current_pos);
+    // the neighbours that are the next step along a trail from pos
+    fn next_steps(&self, pos: &Pos) -> Vec<Pos> {
+        let Some(current_value) = self.get_value(pos) else {
+            return vec![];
+        };
 
-        let directions = [
+        let mut directions = vec![
             Direction::Up,
             Direction::Right,
             Direction::Down,
             Direction::Left,
         ];
-        for direction in directions {
-            let next_pos = match direction {
-                Direction::Up => Pos {
-                    x: current_pos.x - 1,
-                    y: current_pos.y,
-                },
-                Direction::Down => Pos {
-                    x: current_pos.x + 1,
-                    y: current_pos.y,
-                },
-                Direction::Left => Pos {
-                    x: current_pos.x,
-                    y: current_pos.y - 1,
-                },
-                Direction::Right => Pos {
-                    x: current_pos.x,
-                    y: current_pos.y + 1,
-                },
-            };
-
-            let next_value = self.get_value(&next_pos);
-            if next_value == -1 {
-                continue;
-            }
+        if self.rule.diagonal {
+            directions.extend([
+                Direction::UpRight,
+                Direction::DownRight,
+                Direction::DownLeft,
+                Direction::UpLeft,
+            ]);
+        }
 
-            if next_value <= current_value {
-                continue;
-            }
+        directions
+            .iter()
+            .map(|direction| {
+                let (x, y) = match direction {
+                    Direction::Up => (-1, 0),
+                    Direction::Down => (1, 0),
+                    Direction::Left => (0, -1),
+                    Direction::Right => (0, 1),
+                    Direction::UpLeft => (-1, -1),
+                    Direction::UpRight => (-1, 1),
+                    Direction::DownLeft => (1, -1),
+                    Direction::DownRight => (1, 1),
+                };
+                Pos {
+                    x: pos.x + x,
+                    y: pos.y + y,
+                }
+            })
+            .filter(|next| self.get_value(next) == Some(current_value + self.rule.step))
+            .collect()
+    }
 
-            if next_value - current_value > 1 {
-                continue;
+    // works back from the end of the trails one step at a time, so every position only
+    // needs the results of the positions one step further on. Gives the trail ends
+    // reachable from each position and the number of distinct trails to them
+    fn count_trails(&self) -> (HashMap<Pos, HashSet<Pos>>, HashMap<Pos, i64>) {
+        let mut summits: HashMap<Pos, HashSet<Pos>> = HashMap::new();
+        let mut ratings: HashMap<Pos, i64> = HashMap::new();
+
+        let Some(levels) = self.rule.levels() else {
+            return (summits, ratings);
+        };
+
+        let mut by_level: Vec<Vec<Pos>> = vec![vec![]; levels as usize + 1];
+        for pos in self.positions() {
+            let level = self.get_value(&pos).and_then(|v| self.rule.level(v));
+            if let Some(level) = level {
+                by_level[level as usize].push(pos);
             }
+        }
+
+        for pos in &by_level[levels as usize] {
+            summits.insert(*pos, HashSet::from([*pos]));
+            ratings.insert(*pos, 1);
+        }
 
-            if next_value == 9 {
-                if let std::collections::hash_map::Entry::Vacant(e) = visited_nines.entry(next_pos)
-                {
-                    e.insert(1);
-                } else {
-                    let entry = visited_nines.get_mut(&next_pos).unwrap();
-                    *entry += 1;
+        for level in (0..levels as usize).rev() {
+            for pos in &by_level[level] {
+                let mut reachable = HashSet::new();
+                let mut rating = 0;
+                for next in self.next_steps(pos) {
+                    reachable.extend(summits[&next].iter().copied());
+                    rating += ratings[&next];
                 }
+
+                summits.insert(*pos, reachable);
+                ratings.insert(*pos, rating);
+            }
+        }
+
+        (summits, ratings)
+    }
+
+    // every trail from the trailhead to a summit, as the positions along it
+    pub fn trails(&self, trailhead: Pos) -> Vec<Vec<Pos>> {
+        let mut trails = vec![];
+        if self.rule.levels().is_none() {
+            return trails;
+        }
+
+        let mut stack = vec![vec![trailhead]];
+
+        while let Some(trail) = stack.pop() {
+            let last = trail.last().expect("trails always have a trailhead");
+            if self.get_value(last) == Some(self.rule.end) {
+                trails.push(trail);
                 continue;
             }
 
-            self.recurse_path(&next_pos, visited_nines);
+            for next in self.next_steps(last).into_iter().rev() {
+                let mut longer = trail.clone();
+                longer.push(next);
+                stack.push(longer);
+            }
         }
+
+        trails
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::collections::HashSet;
 
     #[test]
     fn pos_should_implement_hash() {
@@ -144,4 +254,172 @@ mod test {
 
         assert_eq!(1, hashset.len());
     }
+
+    #[test]
+    fn trails() {
+        let content = "\
+0123
+1234
+8765
+9876";
+
+        let map = Map::parse(content);
+        let trails = map.trails(Pos { x: 0, y: 0 });
+
+        assert_eq!(16, trails.len());
+        assert!(trails.iter().all(|t| t.len() == 10));
+        assert_eq!(
+            vec![
+                Pos { x: 0, y: 0 },
+                Pos { x: 0, y: 1 },
+                Pos { x: 0, y: 2 },
+                Pos { x: 0, y: 3 },
+                Pos { x: 1, y: 3 },
+                Pos { x: 2, y: 3 },
+                Pos { x: 3, y: 3 },
+                Pos { x: 3, y: 2 },
+                Pos { x: 3, y: 1 },
+                Pos { x: 3, y: 0 },
+            ],
+            trails[0]
+        );
+
+        let (summits, ratings) = map.count_trails();
+        assert_eq!(1, summits[&Pos { x: 0, y: 0 }].len());
+        assert_eq!(16, ratings[&Pos { x: 0, y: 0 }]);
+    }
+
+    #[test]
+    fn edges() {
+        // trails along the edges shouldn't wrap around or step off the map
+        let content = "\
+0987
+1236
+9945";
+
+        let map = Map::parse(content);
+
+        assert_eq!(None, map.get_value(&Pos { x: -1, y: 0 }));
+        assert_eq!(None, map.get_value(&Pos { x: 0, y: 4 }));
+        assert_eq!(1, map.trails(Pos { x: 0, y: 0 }).len());
+        assert_eq!((1, 1), map.get_map_total());
+    }
+
+    #[test]
+    fn impassable_tiles() {
+        let map = Map::parse(
+            "\
+...0...
+...1...
+...2...
+6543456
+7.....7
+8.....8
+9.....9",
+        );
+        assert_eq!(None, map.get_value(&Pos { x: 0, y: 0 }));
+        assert_eq!(2, map.get_map_total().0);
+
+        let map = Map::parse(
+            "\
+..90..9
+...1.98
+...2..7
+6543456
+765.987
+876....
+987....",
+        );
+        assert_eq!((4, 13), map.get_map_total());
+
+        let map = Map::parse(
+            "\
+10..9..
+2...8..
+3...7..
+4567654
+...8..3
+...9..2
+.....01",
+        );
+        assert_eq!(3, map.get_map_total().0);
+
+        let map = Map::parse(
+            "\
+.....0.
+..4321.
+..5..2.
+..6543.
+..7..4.
+..8765.
+..9....",
+        );
+        assert_eq!(3, map.get_map_total().1);
+
+        let map = Map::parse(
+            "\
+012345
+123456
+234567
+345678
+4.6789
+56789.",
+        );
+        assert_eq!(227, map.get_map_total().1);
+    }
+
+    #[test]
+    fn trail_rules() {
+        let content = "\
+0.2.4.6.8.
+.1.3.5.7.9";
+
+        let map = Map::parse(content);
+        assert_eq!((0, 0), map.get_map_total());
+
+        let map = map.with_rule(TrailRule {
+            diagonal: true,
+            ..TrailRule::hiking()
+        });
+        assert_eq!((1, 1), map.get_map_total());
+        assert_eq!(10, map.trails(Pos { x: 0, y: 0 })[0].len());
+
+        // walking back down from the top
+        let map = map.with_rule(TrailRule {
+            step: -1,
+            diagonal: true,
+            start: 9,
+            end: 0,
+        });
+        assert_eq!((1, 1), map.get_map_total());
+
+        // only the even heights, two at a time
+        let map = Map::parse("02468\n13579").with_rule(TrailRule {
+            step: 2,
+            diagonal: false,
+            start: 0,
+            end: 8,
+        });
+        assert_eq!((1, 1), map.get_map_total());
+
+        // the end can't be reached from the start
+        let map = map.with_rule(TrailRule {
+            step: 2,
+            diagonal: false,
+            start: 0,
+            end: 9,
+        });
+        assert_eq!((0, 0), map.get_map_total());
+        assert!(map.trails(Pos { x: 0, y: 0 }).is_empty());
+
+        // staying level never gets anywhere, however long the trail goes back and forth
+        let map = Map::parse("00\n00").with_rule(TrailRule {
+            step: 0,
+            diagonal: false,
+            start: 0,
+            end: 9,
+        });
+        assert_eq!((0, 0), map.get_map_total());
+        assert!(map.trails(Pos { x: 0, y: 0 }).is_empty());
+    }
 }
@@ -1,56 +1,380 @@
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
 
 pub mod puzzle1;
 pub mod puzzle2;
 
-fn parse_content(content: &str) -> Vec<String> {
+fn parse_content(content: &str) -> Vec<u64> {
     content
         .split_whitespace()
-        .map(|str| str.to_string())
+        .map(|str| str.parse().expect("always a number"))
         .collect()
 }
 
-fn run(stones: Vec<String>, blinks: usize) -> i64 {
-    let mut cache: HashMap<String, i64> = HashMap::new();
-    let mut stone_count = 0;
+fn run(stones: Vec<u64>, blinks: usize) -> Result<i64, StoneOverflow> {
+    let summaries = evolve(&stones, blinks, &RuleSet::standard())?;
+    let last = summaries.last().expect("always the starting stones");
+    Ok(last
+        .total
+        .to_u64()
+        .and_then(|total| i64::try_from(total).ok())
+        .expect("the total should fit in an i64"))
+}
+
+#[derive(Debug, PartialEq)]
+struct BlinkSummary {
+    total: StoneCount,
+    distinct: usize,
+}
+
+// a number of stones that never overflows, the totals pass u128::MAX well before 500 blinks
+#[derive(Debug, Clone, Default, PartialEq)]
+struct StoneCount {
+    // least significant first, with no zeros on the end
+    limbs: Vec<u64>,
+}
+
+impl StoneCount {
+    fn add(&mut self, other: &StoneCount) {
+        let mut carry = false;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let right = other.limbs.get(i).copied().unwrap_or_default();
+            if i == self.limbs.len() {
+                self.limbs.push(0);
+            }
+
+            let (sum, overflow) = self.limbs[i].overflowing_add(right);
+            let (sum, carry_overflow) = sum.overflowing_add(carry as u64);
+            self.limbs[i] = sum;
+            carry = overflow || carry_overflow;
+        }
+
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        match self.limbs.as_slice() {
+            [] => Some(0),
+            [value] => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl From<u64> for StoneCount {
+    fn from(value: u64) -> Self {
+        let limbs = if value == 0 { vec![] } else { vec![value] };
+        Self { limbs }
+    }
+}
+
+impl fmt::Display for StoneCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the biggest power of ten that fits in a limb
+        const CHUNK: u128 = 10_u128.pow(19);
+
+        let mut limbs = self.limbs.clone();
+        let mut chunks = vec![];
+        while !limbs.is_empty() {
+            let mut remainder = 0_u128;
+            for limb in limbs.iter_mut().rev() {
+                let value = (remainder << 64) | *limb as u128;
+                *limb = (value / CHUNK) as u64;
+                remainder = value % CHUNK;
+            }
+            chunks.push(remainder as u64);
+
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+
+        let Some((first, rest)) = chunks.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{first}")?;
+        for chunk in rest.iter().rev() {
+            write!(f, "{chunk:019}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// a stone a rule couldn't turn into new stones because they wouldn't fit in a u64
+#[derive(Debug, PartialEq)]
+pub struct StoneOverflow {
+    pub blink: usize,
+    pub stone: u64,
+}
+
+struct Rule {
+    applies: Box<dyn Fn(u64) -> bool>,
+    // None when the new stones don't fit in a u64
+    transform: Box<dyn Fn(u64) -> Option<Vec<u64>>>,
+}
+
+impl Rule {
+    fn new(
+        applies: impl Fn(u64) -> bool + 'static,
+        transform: impl Fn(u64) -> Option<Vec<u64>> + 'static,
+    ) -> Self {
+        Self {
+            applies: Box::new(applies),
+            transform: Box::new(transform),
+        }
+    }
+
+    fn replace(from: u64, to: u64) -> Self {
+        Self::new(move |stone| stone == from, move |_| Some(vec![to]))
+    }
+
+    // splits a stone into equal runs of digits when its digit count allows it
+    fn split_digits(parts: u32, base: u64) -> Self {
+        Self::new(
+            move |stone| split_digits(stone, parts, base).is_some(),
+            move |stone| split_digits(stone, parts, base),
+        )
+    }
+
+    fn multiply(factor: u64) -> Self {
+        Self::new(
+            |_| true,
+            move |stone| Some(vec![stone.checked_mul(factor)?]),
+        )
+    }
+}
+
+// the first rule that applies to a stone wins, a stone no rule applies to stays the same
+struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    fn standard() -> Self {
+        Self {
+            rules: vec![
+                Rule::replace(0, 1),
+                Rule::split_digits(2, 10),
+                Rule::multiply(2024),
+            ],
+        }
+    }
+
+    fn apply(&self, stone: u64) -> Option<Vec<u64>> {
+        self.rules
+            .iter()
+            .find(|rule| (rule.applies)(stone))
+            .map_or_else(|| Some(vec![stone]), |rule| (rule.transform)(stone))
+    }
+}
+
+// index 0 is the stones before any blinking
+fn evolve(
+    stones: &[u64],
+    blinks: usize,
+    rules: &RuleSet,
+) -> Result<Vec<BlinkSummary>, StoneOverflow> {
+    let mut counts: HashMap<u64, StoneCount> = HashMap::new();
     for stone in stones {
-        stone_count += count_stones(stone, blinks, &mut cache);
+        counts.entry(*stone).or_default().add(&StoneCount::from(1));
     }
-    stone_count
+
+    let mut cache: HashMap<u64, Vec<u64>> = HashMap::new();
+    let mut summaries = vec![summarise(&counts)];
+    for blink_number in 1..=blinks {
+        counts = blink(&counts, rules, &mut cache).map_err(|stone| StoneOverflow {
+            blink: blink_number,
+            stone,
+        })?;
+        summaries.push(summarise(&counts));
+    }
+
+    Ok(summaries)
 }
 
-fn count_stones(stone: String, blinks: usize, cache: &mut HashMap<String, i64>) -> i64 {
-    let cache_key = format!("{stone}:{blinks}");
-    if cache.contains_key(&cache_key) {
-        return *cache.get(&cache_key).unwrap();
+fn summarise(counts: &HashMap<u64, StoneCount>) -> BlinkSummary {
+    let mut total = StoneCount::default();
+    for count in counts.values() {
+        total.add(count);
     }
 
-    let mut next_stone = stone.to_string();
-    let mut blinks_remaining = blinks;
-    while blinks_remaining > 0 {
-        if next_stone == "0" {
-            next_stone = "1".to_string();
-            blinks_remaining -= 1;
-        } else if next_stone.len() % 2 == 0 {
-            let middle = next_stone.len() / 2;
-            let new_stone1 = next_stone[0..middle].to_string();
-            let new_stone2 = next_stone[middle..next_stone.len()].to_string();
+    BlinkSummary {
+        total,
+        distinct: counts.len(),
+    }
+}
 
-            let new_stone1: i64 = new_stone1.parse().expect("always a number");
-            let new_stone2: i64 = new_stone2.parse().expect("always a number");
+// fails with the stone a rule couldn't transform
+fn blink(
+    counts: &HashMap<u64, StoneCount>,
+    rules: &RuleSet,
+    cache: &mut HashMap<u64, Vec<u64>>,
+) -> Result<HashMap<u64, StoneCount>, u64> {
+    let mut next: HashMap<u64, StoneCount> = HashMap::with_capacity(counts.len());
+    for (stone, count) in counts {
+        let new_stones = match cache.entry(*stone) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(rules.apply(*stone).ok_or(*stone)?),
+        };
 
-            blinks_remaining -= 1;
-            let mut stone_count = count_stones(new_stone1.to_string(), blinks_remaining, cache);
-            stone_count += count_stones(new_stone2.to_string(), blinks_remaining, cache);
-            cache.insert(cache_key, stone_count);
-            return stone_count;
-        } else {
-            let stone_as_int: i64 = (next_stone.parse::<i64>().expect("always a number")) * 2024;
-            next_stone = stone_as_int.to_string();
-            blinks_remaining -= 1;
+        for new_stone in new_stones {
+            next.entry(*new_stone).or_default().add(count);
         }
     }
 
-    cache.insert(cache_key, 1);
-    1
+    Ok(next)
+}
+
+fn split_digits(stone: u64, parts: u32, base: u64) -> Option<Vec<u64>> {
+    let digits = stone.checked_ilog(base)? + 1;
+    if digits % parts != 0 {
+        return None;
+    }
+
+    let part_size = base.pow(digits / parts);
+    let mut split = vec![];
+    let mut remaining = stone;
+    for _ in 0..parts {
+        split.push(remaining % part_size);
+        remaining /= part_size;
+    }
+    split.reverse();
+
+    Some(split)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn totals(summaries: &[BlinkSummary]) -> Vec<u64> {
+        summaries
+            .iter()
+            .map(|s| s.total.to_u64().expect("small totals"))
+            .collect()
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(None, split_digits(0, 2, 10));
+        assert_eq!(None, split_digits(7, 2, 10));
+        assert_eq!(Some(vec![1, 0]), split_digits(10, 2, 10));
+        assert_eq!(Some(vec![10, 0]), split_digits(1000, 2, 10));
+        assert_eq!(Some(vec![253, 0]), split_digits(253000, 2, 10));
+        assert_eq!(None, split_digits(99999, 2, 10));
+        assert_eq!(Some(vec![12, 34, 56]), split_digits(123456, 3, 10));
+        assert_eq!(None, split_digits(1234, 3, 10));
+        assert_eq!(Some(vec![0b10, 0b11]), split_digits(0b1011, 2, 2));
+    }
+
+    #[test]
+    fn per_blink_totals() {
+        let summaries = evolve(&[125, 17], 6, &RuleSet::standard()).unwrap();
+
+        assert_eq!(vec![2, 3, 4, 5, 9, 13, 22], totals(&summaries));
+
+        // 2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
+        assert_eq!(
+            BlinkSummary {
+                total: StoneCount::from(22),
+                distinct: 15,
+            },
+            summaries[6]
+        );
+    }
+
+    #[test]
+    fn many_blinks() {
+        let summaries = evolve(&[125, 17], 500, &RuleSet::standard()).unwrap();
+
+        assert_eq!(501, summaries.len());
+        assert_eq!("65601038650482", summaries[75].total.to_string());
+        assert_eq!(
+            "3228697720950807773236428359413636851",
+            summaries[200].total.to_string()
+        );
+        assert_eq!(
+            "9332778333171329647192501576620127875703052322448004098317987815134809182249715590744339095",
+            summaries[500].total.to_string()
+        );
+        assert_eq!(54, summaries[500].distinct);
+    }
+
+    #[test]
+    fn stone_counts() {
+        let mut count = StoneCount::from(u64::MAX);
+        count.add(&StoneCount::from(1));
+        assert_eq!(None, count.to_u64());
+        assert_eq!("18446744073709551616", count.to_string());
+
+        count.add(&count.clone());
+        assert_eq!("36893488147419103232", count.to_string());
+
+        assert_eq!("0", StoneCount::default().to_string());
+        assert_eq!(Some(0), StoneCount::from(0).to_u64());
+        assert_eq!(
+            "10000000000000000000",
+            StoneCount::from(10_u64.pow(19)).to_string()
+        );
+    }
+
+    #[test]
+    fn rule_variants() {
+        let rules = RuleSet {
+            rules: vec![
+                Rule::replace(0, 1),
+                Rule::split_digits(3, 10),
+                Rule::multiply(1000),
+            ],
+        };
+        assert_eq!(Some(vec![1]), rules.apply(0));
+        assert_eq!(Some(vec![1, 2, 3]), rules.apply(123));
+        assert_eq!(Some(vec![12000]), rules.apply(12));
+
+        // 100 -> 1 0 0 -> 1000 1 1 -> 1000000 1000 1000
+        let summaries = evolve(&[100], 3, &rules).unwrap();
+        assert_eq!(vec![1, 3, 3, 3], totals(&summaries));
+        assert_eq!(2, summaries[3].distinct);
+
+        let binary = RuleSet {
+            rules: vec![Rule::split_digits(2, 2), Rule::multiply(3)],
+        };
+        // 0 has no digits to split, so it stays a 0 when multiplied
+        assert_eq!(Some(vec![0]), binary.apply(0));
+        assert_eq!(Some(vec![0b10, 0b11]), binary.apply(0b1011));
+        assert_eq!(Some(vec![21]), binary.apply(0b111));
+    }
+
+    #[test]
+    fn no_matching_rule() {
+        let rules = RuleSet {
+            rules: vec![Rule::replace(0, 1)],
+        };
+
+        assert_eq!(Some(vec![5]), rules.apply(5));
+        assert_eq!(vec![1, 1, 1], totals(&evolve(&[0], 2, &rules).unwrap()));
+    }
+
+    #[test]
+    fn overflowing_rules() {
+        // without splitting, the stones keep growing until they don't fit in a u64
+        let rules = RuleSet {
+            rules: vec![Rule::replace(0, 1), Rule::multiply(2024)],
+        };
+
+        assert_eq!(None, rules.apply(u64::MAX / 2000));
+        assert!(evolve(&[0], 6, &rules).is_ok());
+        assert_eq!(
+            Err(StoneOverflow {
+                blink: 7,
+                stone: 2024_u64.pow(5),
+            }),
+            evolve(&[0], 10, &rules)
+        );
+    }
 }
@@ -2,11 +2,11 @@ use crate::read_file;
 
 use super::*;
 
-pub fn solve_day11_puzzle1() -> i64 {
+pub fn solve_day11_puzzle1() -> Result<i64, StoneOverflow> {
     get_result(read_file("day11.txt").as_str())
 }
 
-fn get_result(content: &str) -> i64 {
+fn get_result(content: &str) -> Result<i64, StoneOverflow> {
     let stones = parse_content(content);
     run(stones, 25)
 }
@@ -21,7 +21,19 @@ mod tests {
 
         let result = get_result(content);
 
-        assert_eq!(55312, result);
+        assert_eq!(Ok(55312), result);
+    }
+
+    #[test]
+    fn overflowing_stone() {
+        // 19 digits don't split, and times 2024 is past u64::MAX
+        assert_eq!(
+            Err(StoneOverflow {
+                blink: 1,
+                stone: 1234567890123456789
+            }),
+            get_result("1234567890123456789")
+        );
     }
 
     #[test]
@@ -30,6 +42,6 @@ mod tests {
 
         let result = solve_day11_puzzle1();
 
-        assert_eq!(220999, result);
+        assert_eq!(Ok(220999), result);
     }
 }
@@ -1,11 +1,11 @@
 use super::*;
 use crate::read_file;
 
-pub fn solve_day11_puzzle2() -> i64 {
+pub fn solve_day11_puzzle2() -> Result<i64, StoneOverflow> {
     get_result(read_file("day11.txt").as_str())
 }
 
-fn get_result(content: &str) -> i64 {
+fn get_result(content: &str) -> Result<i64, StoneOverflow> {
     let stones = parse_content(content);
     run(stones, 75)
 }
@@ -20,6 +20,6 @@ mod tests {
 
         let result = solve_day11_puzzle2();
 
-        assert_eq!(261936432123724, result);
+        assert_eq!(Ok(261936432123724), result);
     }
 }
@@ -6,11 +6,231 @@ pub fn solve_puzzle1() -> (i64, i64) {
     get_result(read_file("day12.txt").as_str())
 }
 
+pub fn get_regions() -> Vec<Region> {
+    find_regions(&parse_chars(read_file("day12.txt").as_str()))
+}
+
 fn get_result(content: &str) -> (i64, i64) {
-    let mut sum_by_perimeter = 0;
-    let mut sum_by_corners = 0;
     let grid = parse_chars(content);
+    let regions = find_regions(&grid);
+
+    let sum_by_perimeter = regions.iter().map(Region::price_by_perimeter).sum();
+    let sum_by_corners = regions.iter().map(Region::price_by_sides).sum();
+
+    (sum_by_perimeter, sum_by_corners)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Region {
+    pub plant_type: char,
+    pub plots: HashSet<Pos>,
+    pub area: i64,
+    pub perimeter: i64,
+    pub sides: i64,
+    pub bounds: Bounds,
+}
+
+// top left and bottom right plots of a region, inclusive
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Bounds {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Region {
+    pub fn price_by_perimeter(&self) -> i64 {
+        self.area * self.perimeter
+    }
+
+    pub fn price_by_sides(&self) -> i64 {
+        self.area * self.sides
+    }
+
+    pub fn boundaries(&self) -> Boundaries {
+        let mut outgoing: HashMap<Corner, Vec<Direction>> = HashMap::new();
+
+        // every fence is walked with the region on its right hand side, so the outside
+        // fence runs clockwise and the fences around holes run anti-clockwise
+        for plot in &self.plots {
+            let (x, y) = (plot.x, plot.y);
+            let fences = [
+                (plot.up(), Corner { x, y }, Direction::Right),
+                (
+                    Some(Pos { x, y: y + 1 }),
+                    Corner { x, y: y + 1 },
+                    Direction::Down,
+                ),
+                (
+                    Some(Pos { x: x + 1, y }),
+                    Corner { x: x + 1, y: y + 1 },
+                    Direction::Left,
+                ),
+                (plot.left(), Corner { x: x + 1, y }, Direction::Up),
+            ];
+
+            for (neighbour, start, direction) in fences {
+                if !neighbour.is_some_and(|n| self.plots.contains(&n)) {
+                    outgoing.entry(start).or_default().push(direction);
+                }
+            }
+        }
+
+        let mut boundaries = Boundaries {
+            outer: vec![],
+            inner: vec![],
+        };
+
+        while let Some(start) = outgoing
+            .iter()
+            .filter(|(_, directions)| !directions.is_empty())
+            .map(|(corner, _)| *corner)
+            .min()
+        {
+            let polygon = trace_polygon(&mut outgoing, start);
+            if signed_area(&polygon) < 0 {
+                boundaries.outer = polygon;
+            } else {
+                boundaries.inner.push(polygon);
+            }
+        }
+
+        boundaries.inner.sort();
+        boundaries
+    }
+}
+
+// the corners where a fence changes direction, a region that is only joined up and down
+// and left and right always has exactly one outer polygon
+pub type Polygon = Vec<Corner>;
+
+#[derive(Debug, PartialEq)]
+pub struct Boundaries {
+    pub outer: Polygon,
+    pub inner: Vec<Polygon>,
+}
+
+impl Boundaries {
+    pub fn sides(&self) -> i64 {
+        (self.outer.len() + self.inner.iter().map(Vec::len).sum::<usize>()) as i64
+    }
+}
+
+// a point between plots, the top left of the plot with the same x and y
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Debug, Copy, Clone)]
+pub struct Corner {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    fn turn_right(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    fn turn_left(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    fn step(&self, corner: Corner) -> Corner {
+        match self {
+            Direction::Up => Corner {
+                x: corner.x - 1,
+                y: corner.y,
+            },
+            Direction::Right => Corner {
+                x: corner.x,
+                y: corner.y + 1,
+            },
+            Direction::Down => Corner {
+                x: corner.x + 1,
+                y: corner.y,
+            },
+            Direction::Left => Corner {
+                x: corner.x,
+                y: corner.y - 1,
+            },
+        }
+    }
+}
+
+fn trace_polygon(outgoing: &mut HashMap<Corner, Vec<Direction>>, start: Corner) -> Polygon {
+    let mut polygon = vec![];
+    let mut corner = start;
+    let mut direction = take_direction(outgoing, corner, None);
+
+    // the top left corner of a loop always turns, so it is safe to start and stop there
+    polygon.push(corner);
+
+    loop {
+        corner = direction.step(corner);
+        if corner == start {
+            break;
+        }
+
+        let next = take_direction(outgoing, corner, Some(direction));
+        if next != direction {
+            polygon.push(corner);
+        }
+        direction = next;
+    }
+
+    polygon
+}
+
+fn take_direction(
+    outgoing: &mut HashMap<Corner, Vec<Direction>>,
+    corner: Corner,
+    arriving: Option<Direction>,
+) -> Direction {
+    let directions = outgoing
+        .get_mut(&corner)
+        .expect("every fence should lead to another fence");
+
+    // two plots only touching diagonally aren't joined, so where two fences leave the
+    // same corner we stay tight to the plot we are walking along by turning right
+    let index = match arriving {
+        Some(arriving) => [arriving.turn_right(), arriving, arriving.turn_left()]
+            .iter()
+            .find_map(|d| directions.iter().position(|o| o == d))
+            .expect("every fence should lead to another fence"),
+        None => 0,
+    };
+
+    directions.remove(index)
+}
+
+fn signed_area(polygon: &Polygon) -> i64 {
+    let mut area = 0;
+    for (i, corner) in polygon.iter().enumerate() {
+        let next = polygon[(i + 1) % polygon.len()];
+        area += corner.x as i64 * next.y as i64 - next.x as i64 * corner.y as i64;
+    }
+
+    area
+}
+
+fn find_regions(grid: &Grid) -> Vec<Region> {
+    let mut regions = vec![];
     let mut visited: HashSet<Pos> = HashSet::new();
+
     for (x, row) in grid.iter().enumerate() {
         for (y, plant_type) in row.iter().enumerate() {
             let pos = Pos { x, y };
@@ -18,26 +238,13 @@ fn get_result(content: &str) -> (i64, i64) {
                 continue;
             }
 
-            let mut region = HashSet::new();
-            let mut region_perimeter = HashMap::new();
-            let mut corner_count = 0;
-            walk(
-                &grid,
-                pos,
-                *plant_type,
-                &mut region,
-                &mut region_perimeter,
-                &mut corner_count,
-            );
-            sum_by_perimeter += region.len() as i64 * region_perimeter.values().sum::<i64>();
-            sum_by_corners += region.len() as i64 * corner_count;
-
-            for p in region {
-                visited.insert(p);
-            }
+            let region = walk(grid, pos, *plant_type);
+            visited.extend(region.plots.iter().copied());
+            regions.push(region);
         }
     }
-    (sum_by_perimeter, sum_by_corners)
+
+    regions
 }
 
 type Grid = Vec<Vec<char>>;
@@ -47,9 +254,9 @@ fn parse_chars(content: &str) -> Grid {
 }
 
 #[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
-struct Pos {
-    x: usize,
-    y: usize,
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
 }
 
 impl Pos {
@@ -189,101 +396,302 @@ fn count_corners(grid: &Grid, center: &Pos) -> i64 {
 }
 
 fn get_value(grid: &Grid, pos: Option<Pos>, plant: char) -> bool {
-    if pos.is_some() {
-        let left_up = pos.unwrap();
-        let value = grid[left_up.x][left_up.y];
+    if let Some(pos) = pos {
+        let value = grid[pos.x][pos.y];
         value == plant
     } else {
         false
     }
 }
 
-fn walk(
-    grid: &Grid,
-    pos: Pos,
-    plant_type: char,
-    visited: &mut HashSet<Pos>,
-    perimeters: &mut HashMap<Pos, i64>,
-    corner_count: &mut i64,
-) -> bool {
-    let current_char = grid[pos.x][pos.y];
-    if current_char != plant_type {
-        return false;
+fn walk(grid: &Grid, start: Pos, plant_type: char) -> Region {
+    let mut plots = HashSet::from([start]);
+    let mut queue = vec![start];
+    let mut perimeter = 0;
+    let mut sides = 0;
+    let mut bounds = Bounds {
+        min: start,
+        max: start,
+    };
+
+    while let Some(pos) = queue.pop() {
+        let column_len = grid[pos.x].len();
+        let row_len = grid.len();
+
+        sides += count_corners(grid, &pos);
+
+        bounds.min.x = bounds.min.x.min(pos.x);
+        bounds.min.y = bounds.min.y.min(pos.y);
+        bounds.max.x = bounds.max.x.max(pos.x);
+        bounds.max.y = bounds.max.y.max(pos.y);
+
+        for next in [
+            pos.up(),
+            pos.right(column_len),
+            pos.down(row_len),
+            pos.left(),
+        ] {
+            match next {
+                Some(next) if grid[next.x][next.y] == plant_type => {
+                    if plots.insert(next) {
+                        queue.push(next);
+                    }
+                }
+                // the edge of the map or another plant so a fence is needed
+                _ => perimeter += 1,
+            }
+        }
     }
 
-    if visited.contains(&pos) {
-        return true;
+    Region {
+        plant_type,
+        area: plots.len() as i64,
+        plots,
+        perimeter,
+        sides,
+        bounds,
     }
+}
 
-    visited.insert(pos);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let column_len = grid[pos.x].len();
-    let row_len = grid.len();
+    #[test]
+    fn demo() {
+        let content = "\
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE";
 
-    let up = pos.up();
-    let right = pos.right(column_len);
-    let down = pos.down(row_len);
-    let left = pos.left();
+        let result = get_result(content);
 
-    *corner_count += count_corners(grid, &pos);
+        assert_eq!(1930, result.0);
+        assert_eq!(1206, result.1);
+    }
 
-    if let Some(up) = up {
-        // try going up
-        if !walk(grid, up, plant_type, visited, perimeters, corner_count) {
-            let entry = perimeters.entry(pos).or_default();
-            *entry += 1;
-        }
-    } else {
-        // then we know there is a fence needed
-        let entry = perimeters.entry(pos).or_default();
-        *entry += 1;
+    #[test]
+    fn regions() {
+        let content = "\
+AAAA
+BBCD
+BBCC
+EEEC";
+
+        let grid = parse_chars(content);
+        let regions = find_regions(&grid);
+
+        let summary: Vec<(char, i64, i64, i64)> = regions
+            .iter()
+            .map(|r| (r.plant_type, r.area, r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            vec![
+                ('A', 4, 10, 4),
+                ('B', 4, 8, 4),
+                ('C', 4, 10, 8),
+                ('D', 1, 4, 4),
+                ('E', 3, 8, 4),
+            ],
+            summary
+        );
+
+        let c = &regions[2];
+        assert_eq!(
+            Bounds {
+                min: Pos { x: 1, y: 2 },
+                max: Pos { x: 3, y: 3 },
+            },
+            c.bounds
+        );
+        assert_eq!(40, c.price_by_perimeter());
+        assert_eq!(32, c.price_by_sides());
     }
 
-    if let Some(right) = right {
-        // try going right
-        if !walk(grid, right, plant_type, visited, perimeters, corner_count) {
-            let entry = perimeters.entry(pos).or_default();
-            *entry += 1;
-        }
-    } else {
-        // then we know there is a fence needed
-        let entry = perimeters.entry(pos).or_default();
-        *entry += 1;
+    #[test]
+    fn large_single_region() {
+        let content = vec!["A".repeat(140); 140].join("\n");
+
+        let grid = parse_chars(&content);
+        let regions = find_regions(&grid);
+
+        assert_eq!(1, regions.len());
+        assert_eq!(140 * 140, regions[0].area);
+        assert_eq!(140 * 4, regions[0].perimeter);
+        assert_eq!(4, regions[0].sides);
     }
 
-    if let Some(down) = down {
-        // try going down
-        if !walk(grid, down, plant_type, visited, perimeters, corner_count) {
-            let entry = perimeters.entry(pos).or_default();
-            *entry += 1;
-        }
-    } else {
-        // then we know there is a fence needed
-        let entry = perimeters.entry(pos).or_default();
-        *entry += 1;
+    #[test]
+    fn most_expensive_region() {
+        let content = "\
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE";
+
+        let grid = parse_chars(content);
+        let regions = find_regions(&grid);
+
+        assert_eq!(11, regions.len());
+
+        let most_expensive = regions
+            .iter()
+            .max_by_key(|r| r.price_by_perimeter())
+            .unwrap();
+        assert_eq!('C', most_expensive.plant_type);
+        assert_eq!(392, most_expensive.price_by_perimeter());
+    }
+
+    #[test]
+    fn holes() {
+        let content = "\
+OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO";
+
+        let result = get_result(content);
+        assert_eq!(772, result.0);
+        assert_eq!(436, result.1);
+
+        let regions = find_regions(&parse_chars(content));
+        let boundaries = regions[0].boundaries();
+        assert_eq!(
+            vec![
+                Corner { x: 0, y: 0 },
+                Corner { x: 0, y: 5 },
+                Corner { x: 5, y: 5 },
+                Corner { x: 5, y: 0 },
+            ],
+            boundaries.outer
+        );
+        assert_eq!(4, boundaries.inner.len());
+        assert_eq!(
+            vec![
+                Corner { x: 1, y: 1 },
+                Corner { x: 2, y: 1 },
+                Corner { x: 2, y: 2 },
+                Corner { x: 1, y: 2 },
+            ],
+            boundaries.inner[0]
+        );
     }
 
-    if let Some(left) = left {
-        // try going left
-        if !walk(grid, left, plant_type, visited, perimeters, corner_count) {
-            let entry = perimeters.entry(pos).or_default();
-            *entry += 1;
+    #[test]
+    fn enclosed_rows() {
+        let content = "\
+EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE";
+
+        let result = get_result(content);
+        assert_eq!(236, result.1);
+
+        let regions = find_regions(&parse_chars(content));
+        let e = regions[0].boundaries();
+        assert_eq!(12, e.outer.len());
+        assert!(e.inner.is_empty());
+    }
+
+    #[test]
+    fn diagonal_holes() {
+        let content = "\
+AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA";
+
+        let result = get_result(content);
+        assert_eq!(368, result.1);
+
+        let regions = find_regions(&parse_chars(content));
+        assert_eq!(3, regions.len());
+
+        // the two B regions touch at a corner, so the A region sees them as one hole
+        let a = regions[0].boundaries();
+        assert_eq!(4, a.outer.len());
+        assert_eq!(1, a.inner.len());
+        assert_eq!(8, a.inner[0].len());
+        assert_eq!(12, a.sides());
+
+        for b in &regions[1..] {
+            let boundaries = b.boundaries();
+            assert_eq!(4, boundaries.outer.len());
+            assert!(boundaries.inner.is_empty());
         }
-    } else {
-        // then we know there is a fence needed
-        let entry = perimeters.entry(pos).or_default();
-        *entry += 1;
     }
 
-    true
-}
+    #[test]
+    fn diagonal_plots() {
+        let content = "\
+AB
+BA";
+
+        let regions = find_regions(&parse_chars(content));
+        assert_eq!(4, regions.len());
+        for region in regions {
+            assert_eq!(4, region.sides);
+            assert_eq!(4, region.boundaries().sides());
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn nested_regions() {
+        let content = "\
+AAAAAAA
+ABBBBBA
+ABCCCBA
+ABCACBA
+ABCCCBA
+ABBBBBA
+AAAAAAA";
+
+        let regions = find_regions(&parse_chars(content));
+        assert_eq!(4, regions.len());
+
+        let summary: Vec<(char, usize, Vec<usize>)> = regions
+            .iter()
+            .map(|r| {
+                let boundaries = r.boundaries();
+                (
+                    r.plant_type,
+                    boundaries.outer.len(),
+                    boundaries.inner.iter().map(Vec::len).collect(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                ('A', 4, vec![4]),
+                ('B', 4, vec![4]),
+                ('C', 4, vec![4]),
+                ('A', 4, vec![]),
+            ],
+            summary
+        );
+    }
 
     #[test]
-    fn demo() {
+    fn traced_sides_match_corners() {
         let content = "\
 RRRRIICCFF
 RRRRIICCCF
@@ -296,10 +704,9 @@ MIIIIIJJEE
 MIIISIJEEE
 MMMISSJEEE";
 
-        let result = get_result(content);
-
-        assert_eq!(1930, result.0);
-        assert_eq!(1206, result.1);
+        for region in find_regions(&parse_chars(content)) {
+            assert_eq!(region.sides, region.boundaries().sides());
+        }
     }
 
     #[test]
@@ -1,51 +1,68 @@
-use std::ops::Add;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
 
 use crate::read_file;
 
-pub fn solve_puzzle_1() -> i128 {
-    get_result(&read_file("day13.txt"), 0)
+const DEFAULT_COSTS: [i64; 2] = [3, 1];
+
+pub fn solve_puzzle_1() -> Result<i128, MissingCost> {
+    get_result(&read_file("day13.txt"), &DEFAULT_COSTS, 0, Some(100))
 }
 
-pub fn solve_puzzle_2() -> i128 {
-    get_result(&read_file("day13.txt"), 10000000000000)
+pub fn solve_puzzle_2() -> Result<i128, MissingCost> {
+    get_result(
+        &read_file("day13.txt"),
+        &DEFAULT_COSTS,
+        10000000000000,
+        None,
+    )
 }
 
-fn get_result(content: &str, additional_target_distance: i64) -> i128 {
-    let games = parse_content(content);
+fn get_result(
+    content: &str,
+    costs: &[i64],
+    additional_target_distance: i64,
+    press_limit: Option<i64>,
+) -> Result<i128, MissingCost> {
+    let games = parse_content(content, costs)?;
 
     let mut sum: i128 = 0;
 
     for game in games {
         let result = game
-            .find_cheapest_result(additional_target_distance)
+            .find_cheapest_result(additional_target_distance, press_limit)
             .unwrap_or_default();
 
-        sum += result as i128;
+        sum += result;
     }
 
-    sum
+    Ok(sum)
 }
 
-fn parse_content(content: &str) -> Vec<Game> {
+fn parse_content(content: &str, costs: &[i64]) -> Result<Vec<Game>, MissingCost> {
     let mut games = vec![];
     let mut game_string = String::new();
 
-    let mut init = true;
     for line in content.lines().filter(|l| !l.is_empty()) {
-        if !init && line.starts_with("Button A") {
-            games.push(Game::parse(&game_string));
-            game_string = String::new()
-        }
-        init = false;
-
         game_string.push_str(line);
         game_string.push('\n');
+
+        // the prize always closes off a game, however many buttons came before it
+        if line.starts_with("Prize") {
+            games.push(Game::parse(&game_string, costs)?);
+            game_string = String::new()
+        }
     }
 
-    // add the last game
-    games.push(Game::parse(&game_string));
+    Ok(games)
+}
 
-    games
+// a game has more buttons than there are costs, button 2 being Button C
+#[derive(Debug, PartialEq)]
+pub struct MissingCost {
+    pub button: usize,
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -60,73 +77,689 @@ impl Pos {
     }
 }
 
-impl Add for Pos {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
-    }
+#[derive(Debug, Copy, Clone)]
+struct Button {
+    offset: Pos,
+    cost: i64,
 }
 
 #[derive(Debug)]
 struct Game {
-    button_a: Pos,
-    button_b: Pos,
+    buttons: Vec<Button>,
     prize: Pos,
 }
 
 impl Game {
-    fn parse(value: &str) -> Self {
-        let lines: Vec<&str> = value.lines().collect();
-        let button_a_splits: Vec<&str> = lines[0].split(':').last().unwrap().split(',').collect();
-        let button_b_splits: Vec<&str> = lines[1].split(':').last().unwrap().split(',').collect();
-        let prize_splits: Vec<&str> = lines[2].split(':').last().unwrap().split(',').collect();
-
-        let button_a = Pos::new(
-            button_a_splits[0].replace("X+", "").trim().parse().unwrap(),
-            button_a_splits[1].replace("Y+", "").trim().parse().unwrap(),
-        );
-        let button_b = Pos::new(
-            button_b_splits[0].replace("X+", "").trim().parse().unwrap(),
-            button_b_splits[1].replace("Y+", "").trim().parse().unwrap(),
-        );
-        let prize = Pos::new(
-            prize_splits[0].replace("X=", "").trim().parse().unwrap(),
-            prize_splits[1].replace("Y=", "").trim().parse().unwrap(),
+    fn parse(value: &str, costs: &[i64]) -> Result<Self, MissingCost> {
+        let mut buttons = vec![];
+        let mut prize = None;
+
+        for line in value.lines() {
+            let (label, values) = line.split_once(':').expect("should always have a label");
+            let splits: Vec<&str> = values.split(',').collect();
+
+            if label.starts_with("Button") {
+                let offset = Pos::new(
+                    splits[0].replace("X+", "").trim().parse().unwrap(),
+                    splits[1].replace("Y+", "").trim().parse().unwrap(),
+                );
+                let cost = *costs.get(buttons.len()).ok_or(MissingCost {
+                    button: buttons.len(),
+                })?;
+
+                buttons.push(Button { offset, cost });
+            } else if label == "Prize" {
+                prize = Some(Pos::new(
+                    splits[0].replace("X=", "").trim().parse().unwrap(),
+                    splits[1].replace("Y=", "").trim().parse().unwrap(),
+                ));
+            }
+        }
+
+        Ok(Self {
+            buttons,
+            prize: prize.expect("should always have a prize"),
+        })
+    }
+
+    fn find_cheapest_result(
+        &self,
+        additional_target_distance: i64,
+        press_limit: Option<i64>,
+    ) -> Option<i128> {
+        let presses = self.find_cheapest_presses(additional_target_distance, press_limit)?;
+
+        Some(
+            presses
+                .iter()
+                .zip(&self.buttons)
+                .map(|(presses, button)| presses * button.cost as i128)
+                .sum(),
+        )
+    }
+
+    // the cheapest presses of every button, assuming no button costs less than nothing
+    fn find_cheapest_presses(
+        &self,
+        additional_target_distance: i64,
+        press_limit: Option<i64>,
+    ) -> Option<Vec<i128>> {
+        let target = (
+            self.prize.x as i128 + additional_target_distance as i128,
+            self.prize.y as i128 + additional_target_distance as i128,
         );
+        let limit = press_limit.map(|l| l as i128);
+
+        // a button that doesn't move the claw never helps, so it's never pressed
+        let moving: Vec<usize> = (0..self.buttons.len())
+            .filter(|i| self.buttons[*i].offset != Pos::new(0, 0))
+            .collect();
+        let buttons: Vec<Button> = moving.iter().map(|i| self.buttons[*i]).collect();
 
-        Self {
-            button_a,
-            button_b,
-            prize,
+        let mut presses = vec![0; self.buttons.len()];
+        if buttons.is_empty() {
+            return (target == (0, 0)).then_some(presses);
+        }
+
+        let relaxed = relaxed_optimum(&buttons, target, limit)?;
+        let found = match lattice_presses(&buttons, target, limit, &relaxed) {
+            Lattice::Cheapest(found) => found,
+            Lattice::Unreachable => return None,
+            // only close to the edges of what the buttons can reach, where the presses are
+            // small enough to search near the relaxed optimum
+            Lattice::Unsettled => {
+                let ranges = press_ranges(&buttons, &relaxed.presses, limit);
+                let mut found = vec![0; buttons.len()];
+                let mut best = None;
+                search_presses(&buttons, 0, target, limit, &ranges, &mut found, &mut best);
+
+                best?.1
+            }
+        };
+
+        for (i, n) in moving.iter().zip(found) {
+            presses[*i] = n;
+        }
+
+        Some(presses)
+    }
+}
+
+// how many times each button could be pressed in a cheapest solution. Some cheapest
+// solution is always within buttons * delta presses of the cheapest one where buttons can
+// be pressed fractions of a time, delta being the biggest determinant made from the
+// offsets (Cook, Gerards, Schrijver and Tardos)
+fn press_ranges(
+    buttons: &[Button],
+    relaxed: &[Fraction],
+    limit: Option<i128>,
+) -> Vec<(i128, i128)> {
+    let offsets: Vec<(i128, i128)> = buttons
+        .iter()
+        .map(|b| (b.offset.x as i128, b.offset.y as i128))
+        .collect();
+    let mut delta = 1;
+    for (i, (ax, ay)) in offsets.iter().enumerate() {
+        delta = delta.max(ax.abs()).max(ay.abs());
+        for (bx, by) in &offsets[i + 1..] {
+            delta = delta.max((ax * by - ay * bx).abs());
+        }
+    }
+    let distance = buttons.len() as i128 * delta;
+
+    relaxed
+        .iter()
+        .map(|(numerator, denominator)| {
+            let low = div_ceil(numerator - distance * denominator, *denominator).max(0);
+            let mut high = div_floor(numerator + distance * denominator, *denominator);
+            if let Some(limit) = limit {
+                high = high.min(limit);
+            }
+            (low, high)
+        })
+        .collect()
+}
+
+// numerator and positive denominator
+type Fraction = (i128, i128);
+
+// the cheapest presses when buttons can be pressed fractions of a time. That's always at a
+// corner, where at most two independent buttons, the basis, are pressed a fraction of a
+// time and the rest either aren't pressed or are pressed the limit
+struct Relaxed {
+    presses: Vec<Fraction>,
+    // None when no two buttons are independent
+    basis: Option<(usize, usize)>,
+}
+
+fn relaxed_optimum(
+    buttons: &[Button],
+    target: (i128, i128),
+    limit: Option<i128>,
+) -> Option<Relaxed> {
+    let count = buttons.len();
+    let offset = |i: usize| (buttons[i].offset.x as i128, buttons[i].offset.y as i128);
+
+    let mut bases: Vec<Vec<usize>> = vec![vec![]];
+    for i in 0..count {
+        bases.push(vec![i]);
+        for j in i + 1..count {
+            let ((ax, ay), (bx, by)) = (offset(i), offset(j));
+            if ax * by - ay * bx != 0 {
+                bases.push(vec![i, j]);
+            }
         }
     }
 
-    fn find_cheapest_result(&self, additional_target_distance: i64) -> Option<i64> {
-        const A_COST: i64 = 3;
-        const B_COST: i64 = 1;
+    let mut best: Option<((Fraction, bool), Relaxed)> = None;
+    for basis in bases {
+        let others: Vec<usize> = (0..count).filter(|i| !basis.contains(i)).collect();
+        let choices = if limit.is_some() {
+            1 << others.len()
+        } else {
+            1
+        };
+
+        for choice in 0..choices {
+            let mut presses = vec![(0, 1); count];
+            let mut remaining = target;
+            for (bit, i) in others.iter().enumerate() {
+                if choice & (1 << bit) != 0 {
+                    let limit = limit.expect("only chosen with a limit");
+                    let (x, y) = offset(*i);
+                    presses[*i] = (limit, 1);
+                    remaining = (remaining.0 - limit * x, remaining.1 - limit * y);
+                }
+            }
+
+            let solved = match basis.as_slice() {
+                [] => remaining == (0, 0),
+                [i] => {
+                    let (x, y) = offset(*i);
+                    if x * remaining.1 != y * remaining.0 {
+                        false
+                    } else {
+                        presses[*i] = if x != 0 {
+                            (remaining.0, x)
+                        } else {
+                            (remaining.1, y)
+                        };
+                        true
+                    }
+                }
+                [i, j] => {
+                    let ((ax, ay), (bx, by)) = (offset(*i), offset(*j));
+                    let determinant = ax * by - ay * bx;
+                    presses[*i] = (remaining.0 * by - remaining.1 * bx, determinant);
+                    presses[*j] = (ax * remaining.1 - ay * remaining.0, determinant);
+                    true
+                }
+                _ => unreachable!("two buttons are always enough to reach any point"),
+            };
+            if !solved {
+                continue;
+            }
+
+            for (numerator, denominator) in presses.iter_mut() {
+                if *denominator < 0 {
+                    *numerator = -*numerator;
+                    *denominator = -*denominator;
+                }
+            }
+            let in_range = presses.iter().all(|(numerator, denominator)| {
+                *numerator >= 0 && limit.is_none_or(|limit| *numerator <= limit * denominator)
+            });
+            if !in_range {
+                continue;
+            }
+
+            // every denominator divides the basis determinant, so that's a common one
+            let denominator = presses.iter().map(|(_, d)| *d).max().unwrap_or(1);
+            let cost = presses
+                .iter()
+                .zip(buttons)
+                .map(|((n, d), button)| n * (denominator / d) * button.cost as i128)
+                .sum::<i128>();
 
-        let prize = self.prize
-            + Pos {
-                x: additional_target_distance,
-                y: additional_target_distance,
+            // a corner can have several bases, and only one where no other button would
+            // make it cheaper is any use to lattice_presses
+            let basis = match basis.as_slice() {
+                [i, j] => Some((*i, *j)),
+                _ => None,
             };
+            let optimal = basis.is_some_and(|(i, j)| {
+                reduced_costs(buttons, i, j).iter().zip(&presses).all(
+                    |(reduced, (numerator, _))| {
+                        if *numerator == 0 {
+                            *reduced >= 0
+                        } else {
+                            *reduced <= 0
+                        }
+                    },
+                )
+            });
+
+            let cheaper =
+                best.as_ref()
+                    .is_none_or(|(((best_cost, best_denominator), best_optimal), _)| {
+                        let (cost, best_cost) = (cost * best_denominator, best_cost * denominator);
+                        cost < best_cost || (cost == best_cost && optimal && !best_optimal)
+                    });
+            if cheaper {
+                best = Some((((cost, denominator), optimal), Relaxed { presses, basis }));
+            }
+        }
+    }
+
+    best.map(|(_, relaxed)| relaxed)
+}
+
+// how much each button changes the cost when the basis makes up for its presses, times the
+// size of the basis determinant
+fn reduced_costs(buttons: &[Button], i: usize, j: usize) -> Vec<i128> {
+    let offset = |b: &Button| (b.offset.x as i128, b.offset.y as i128);
+    let ((ax, ay), (bx, by)) = (offset(&buttons[i]), offset(&buttons[j]));
+    let (a_cost, b_cost) = (buttons[i].cost as i128, buttons[j].cost as i128);
+    let determinant = ax * by - ay * bx;
+
+    buttons
+        .iter()
+        .map(|button| {
+            let (x, y) = offset(button);
+            let reduced = button.cost as i128 * determinant
+                - a_cost * (x * by - y * bx)
+                - b_cost * (ax * y - ay * x);
+            reduced * determinant.signum()
+        })
+        .collect()
+}
+
+enum Lattice {
+    Cheapest(Vec<i128>),
+    Unreachable,
+    // the basis would have to be pressed fewer than no times or more than the limit
+    Unsettled,
+}
+
+// the cheapest whole presses, starting from the relaxed optimum's basis. Every solution
+// costs the relaxed optimum plus each other button's reduced cost for every press, and
+// whether the basis can make up the rest in whole presses only depends on which of the
+// |determinant| remainders modulo the lattice the basis spans the other presses land on.
+// So a shortest path over those remainders to the target's is a lower bound on the cost,
+// and the cheapest solution when the basis presses it leaves are in range. That's always
+// the case far enough from the edges of what the buttons can reach, however many buttons
+// there are
+fn lattice_presses(
+    buttons: &[Button],
+    target: (i128, i128),
+    limit: Option<i128>,
+    relaxed: &Relaxed,
+) -> Lattice {
+    let Some((i, j)) = relaxed.basis else {
+        return Lattice::Unsettled;
+    };
+    let offset = |b: &Button| (b.offset.x as i128, b.offset.y as i128);
+    let ((ax, ay), (bx, by)) = (offset(&buttons[i]), offset(&buttons[j]));
+    let determinant = ax * by - ay * bx;
+    // the basis presses to reach a point, times the determinant
+    let solve = |(x, y): (i128, i128)| (x * by - y * bx, ax * y - ay * x);
+    let remainder = |point: (i128, i128)| {
+        let (a, b) = solve(point);
+        (a.rem_euclid(determinant), b.rem_euclid(determinant))
+    };
+
+    // a button pressed the limit is walked back from the limit instead, so every step costs
+    // nothing or more
+    let reduced = reduced_costs(buttons, i, j);
+    let mut remaining = target;
+    let mut steps = vec![];
+    for (k, button) in buttons.iter().enumerate() {
+        if k == i || k == j {
+            continue;
+        }
+        let (x, y) = offset(button);
+        if relaxed.presses[k].0 == 0 {
+            steps.push((k, (x, y), reduced[k]));
+        } else {
+            let limit = limit.expect("only pressed the limit with a limit");
+            remaining = (remaining.0 - limit * x, remaining.1 - limit * y);
+            steps.push((k, (-x, -y), -reduced[k]));
+        }
+    }
+    if steps.iter().any(|(_, _, cost)| *cost < 0) {
+        return Lattice::Unsettled;
+    }
+
+    // the cost to each remainder and the step that got there first
+    let goal = remainder(remaining);
+    let mut reached: HashMap<(i128, i128), (i128, Option<usize>)> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    reached.insert((0, 0), (0, None));
+    queue.push(Reverse((0, (0, 0))));
+    while let Some(Reverse((cost, current))) = queue.pop() {
+        if current == goal {
+            break;
+        }
+        if reached[&current].0 < cost {
+            continue;
+        }
+
+        for (step, (_, (x, y), step_cost)) in steps.iter().enumerate() {
+            let next = (
+                (current.0 + x * by - y * bx).rem_euclid(determinant),
+                (current.1 + ax * y - ay * x).rem_euclid(determinant),
+            );
+            let next_cost = cost + step_cost;
+            if reached
+                .get(&next)
+                .is_none_or(|(known, _)| next_cost < *known)
+            {
+                reached.insert(next, (next_cost, Some(step)));
+                queue.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+    if !reached.contains_key(&goal) {
+        return Lattice::Unreachable;
+    }
+
+    let mut taken = vec![0; steps.len()];
+    let mut current = goal;
+    while let (_, Some(step)) = reached[&current] {
+        taken[step] += 1;
+        let (x, y) = steps[step].1;
+        current = (
+            (current.0 - (x * by - y * bx)).rem_euclid(determinant),
+            (current.1 - (ax * y - ay * x)).rem_euclid(determinant),
+        );
+    }
+
+    let mut presses = vec![0; buttons.len()];
+    for ((k, (x, y), _), n) in steps.iter().zip(taken) {
+        presses[*k] = if relaxed.presses[*k].0 == 0 {
+            n
+        } else {
+            limit.expect("only pressed the limit with a limit") - n
+        };
+        remaining = (remaining.0 - n * x, remaining.1 - n * y);
+    }
+    let (a, b) = solve(remaining);
+    presses[i] = a / determinant;
+    presses[j] = b / determinant;
+
+    let in_range = presses
+        .iter()
+        .all(|n| *n >= 0 && limit.is_none_or(|limit| *n <= limit));
+    if !in_range {
+        return Lattice::Unsettled;
+    }
+
+    Lattice::Cheapest(presses)
+}
+
+// picks a press count for each button in turn and solves the last two buttons exactly
+fn search_presses(
+    buttons: &[Button],
+    index: usize,
+    target: (i128, i128),
+    limit: Option<i128>,
+    ranges: &[(i128, i128)],
+    presses: &mut Vec<i128>,
+    best: &mut Option<(i128, Vec<i128>)>,
+) {
+    let cost: i128 = presses[..index]
+        .iter()
+        .zip(buttons)
+        .map(|(n, button)| n * button.cost as i128)
+        .sum();
+    if let Some((best_cost, _)) = best {
+        if cost >= *best_cost {
+            return;
+        }
+    }
+
+    let mut record = |presses: &Vec<i128>, cost: i128| match best {
+        Some((best_cost, _)) if *best_cost <= cost => {}
+        _ => *best = Some((cost, presses.clone())),
+    };
+
+    match buttons.len() - index {
+        0 => {
+            if target == (0, 0) {
+                record(presses, cost);
+            }
+        }
+        1 => {
+            let button = &buttons[index];
+            if let Some(n) = solve_single(button, target, limit) {
+                presses[index] = n;
+                record(presses, cost + n * button.cost as i128);
+                presses[index] = 0;
+            }
+        }
+        2 => {
+            let (a, b) = (&buttons[index], &buttons[index + 1]);
+            if let Some((n_a, n_b)) = solve_pair(a, b, target, limit) {
+                presses[index] = n_a;
+                presses[index + 1] = n_b;
+                record(presses, cost + n_a * a.cost as i128 + n_b * b.cost as i128);
+                presses[index] = 0;
+                presses[index + 1] = 0;
+            }
+        }
+        _ => {
+            let button = &buttons[index];
+            let (x, y) = (button.offset.x as i128, button.offset.y as i128);
+
+            let (low, mut high) = ranges[index];
+            if x > 0 {
+                high = high.min(target.0 / x);
+            }
+            if y > 0 {
+                high = high.min(target.1 / y);
+            }
+
+            for n in low..=high {
+                presses[index] = n;
+                search_presses(
+                    buttons,
+                    index + 1,
+                    (target.0 - n * x, target.1 - n * y),
+                    limit,
+                    ranges,
+                    presses,
+                    best,
+                );
+            }
+            presses[index] = 0;
+        }
+    }
+}
+
+fn solve_single(button: &Button, target: (i128, i128), limit: Option<i128>) -> Option<i128> {
+    let (x, y) = (button.offset.x as i128, button.offset.y as i128);
+
+    let n = if x != 0 {
+        target.0 / x
+    } else if y != 0 {
+        target.1 / y
+    } else {
+        0
+    };
+
+    if n < 0 || limit.is_some_and(|limit| n > limit) {
+        return None;
+    }
+
+    if n * x != target.0 || n * y != target.1 {
+        return None;
+    }
+
+    Some(n)
+}
+
+fn solve_pair(
+    a: &Button,
+    b: &Button,
+    target: (i128, i128),
+    limit: Option<i128>,
+) -> Option<(i128, i128)> {
+    let (ax, ay) = (a.offset.x as i128, a.offset.y as i128);
+    let (bx, by) = (b.offset.x as i128, b.offset.y as i128);
+    let (px, py) = target;
+    let (a_cost, b_cost) = (a.cost as i128, b.cost as i128);
+
+    let determinant = ax * by - ay * bx;
+
+    let (n_a, n_b) = if determinant != 0 {
+        // the buttons are independent so there is exactly one candidate
+        let n_a_numerator = px * by - py * bx;
+        let n_b_numerator = ax * py - ay * px;
+        if n_a_numerator % determinant != 0 || n_b_numerator % determinant != 0 {
+            return None;
+        }
 
-        let n_b = (self.button_a.x * prize.y - self.button_a.y * prize.x)
-            / (self.button_b.y * self.button_a.x - self.button_b.x * self.button_a.y);
-        let rem_b = (self.button_a.x * prize.y - self.button_a.y * prize.x)
-            % (self.button_b.y * self.button_a.x - self.button_b.x * self.button_a.y);
-        let n_a = (prize.x - n_b * self.button_b.x) / self.button_a.x;
-        let rem_a = (prize.x - n_b * self.button_b.x) % self.button_a.x;
+        (n_a_numerator / determinant, n_b_numerator / determinant)
+    } else {
+        // the buttons move along the same line, so the prize has to be on that line too and
+        // we need the cheapest of the possibly many combinations
+        if ax * py - ay * px != 0 || bx * py - by * px != 0 {
+            return None;
+        }
+
+        if ax != 0 || bx != 0 {
+            solve_line(ax, bx, px, limit, a_cost, b_cost)?
+        } else {
+            solve_line(ay, by, py, limit, a_cost, b_cost)?
+        }
+    };
 
-        if rem_b != 0 || rem_a != 0 {
+    if n_a < 0 || n_b < 0 {
+        return None;
+    }
+
+    if let Some(limit) = limit {
+        if n_a > limit || n_b > limit {
             return None;
         }
+    }
 
-        Some((n_a * A_COST) + (n_b * B_COST))
+    // make sure the combination lands on the prize in both directions
+    if n_a * ax + n_b * bx != px || n_a * ay + n_b * by != py {
+        return None;
+    }
+
+    Some((n_a, n_b))
+}
+
+// finds the cheapest non-negative n_a and n_b where n_a * a + n_b * b = target
+fn solve_line(
+    a: i128,
+    b: i128,
+    target: i128,
+    limit: Option<i128>,
+    a_cost: i128,
+    b_cost: i128,
+) -> Option<(i128, i128)> {
+    if a == 0 && b == 0 {
+        return if target == 0 { Some((0, 0)) } else { None };
+    }
+
+    if a == 0 {
+        return if target % b == 0 {
+            Some((0, target / b))
+        } else {
+            None
+        };
+    }
+
+    if b == 0 {
+        return if target % a == 0 {
+            Some((target / a, 0))
+        } else {
+            None
+        };
+    }
+
+    let (gcd, x, y) = extended_gcd(a, b);
+    if target % gcd != 0 {
+        return None;
+    }
+
+    // every solution is (n_a0 + k * step_a, n_b0 - k * step_b)
+    let n_a0 = x * (target / gcd);
+    let n_b0 = y * (target / gcd);
+    let step_a = b / gcd;
+    let step_b = a / gcd;
+
+    let mut low = i128::MIN;
+    let mut high = i128::MAX;
+
+    let mut restrict = |value: i128, step: i128, min: i128, max: Option<i128>| {
+        // min <= value + k * step <= max
+        let (lower, upper) = if step > 0 {
+            (
+                div_ceil(min - value, step),
+                max.map(|max| div_floor(max - value, step)),
+            )
+        } else {
+            (
+                max.map(|max| div_ceil(max - value, step))
+                    .unwrap_or(i128::MIN),
+                Some(div_floor(min - value, step)),
+            )
+        };
+        low = low.max(lower);
+        if let Some(upper) = upper {
+            high = high.min(upper);
+        }
+    };
+
+    restrict(n_a0, step_a, 0, limit);
+    restrict(n_b0, -step_b, 0, limit);
+
+    if low > high {
+        return None;
+    }
+
+    // the cost changes linearly with k, so the cheapest is at one end of the range
+    let cost_per_step = a_cost * step_a - b_cost * step_b;
+    let k = if cost_per_step >= 0 { low } else { high };
+    if k == i128::MIN || k == i128::MAX {
+        return None;
+    }
+
+    Some((n_a0 + k * step_a, n_b0 - k * step_b))
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        if a < 0 {
+            (-a, -1, 0)
+        } else {
+            (a, 1, 0)
+        }
+    } else {
+        let (gcd, x, y) = extended_gcd(b, a % b);
+        (gcd, y, x - (a / b) * y)
+    }
+}
+
+fn div_floor(a: i128, b: i128) -> i128 {
+    let quotient = a / b;
+    if (a % b != 0) && ((a < 0) != (b < 0)) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+fn div_ceil(a: i128, b: i128) -> i128 {
+    let quotient = a / b;
+    if (a % b != 0) && ((a < 0) == (b < 0)) {
+        quotient + 1
+    } else {
+        quotient
     }
 }
 
@@ -153,9 +786,264 @@ Button A: X+69, Y+23
 Button B: X+27, Y+71
 Prize: X=18641, Y=10279";
 
-        let result = get_result(content, 0);
+        let result = get_result(content, &DEFAULT_COSTS, 0, Some(100));
+
+        assert_eq!(Ok(480), result);
+    }
+
+    #[test]
+    fn press_limit() {
+        let game = Game::parse(
+            "\
+Button A: X+1, Y+1
+Button B: X+2, Y+3
+Prize: X=150, Y=150",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+
+        assert_eq!(None, game.find_cheapest_result(0, Some(100)));
+        assert_eq!(Some(450), game.find_cheapest_result(0, None));
+    }
+
+    #[test]
+    fn negative_presses() {
+        let game = Game::parse(
+            "\
+Button A: X+2, Y+1
+Button B: X+1, Y+2
+Prize: X=1, Y=5",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+
+        assert_eq!(None, game.find_cheapest_result(0, None));
+    }
+
+    #[test]
+    fn collinear_buttons() {
+        // A is cheaper per distance here
+        let game = Game::parse(
+            "\
+Button A: X+4, Y+4
+Button B: X+1, Y+1
+Prize: X=10, Y=10",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+        assert_eq!(Some(vec![2, 2]), game.find_cheapest_presses(0, None));
+        assert_eq!(Some(8), game.find_cheapest_result(0, None));
+
+        // B is cheaper per distance here
+        let game = Game::parse(
+            "\
+Button A: X+2, Y+2
+Button B: X+1, Y+1
+Prize: X=10, Y=10",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+        assert_eq!(Some(10), game.find_cheapest_result(0, None));
+
+        // not reachable because of the step sizes
+        let game = Game::parse(
+            "\
+Button A: X+4, Y+4
+Button B: X+6, Y+6
+Prize: X=9, Y=9",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+        assert_eq!(None, game.find_cheapest_result(0, None));
+
+        // not on the line
+        let game = Game::parse(
+            "\
+Button A: X+1, Y+2
+Button B: X+2, Y+4
+Prize: X=10, Y=10",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+        assert_eq!(None, game.find_cheapest_result(0, None));
+
+        // large offsets are solved without searching every combination
+        let game = Game::parse(
+            "\
+Button A: X+3, Y+3
+Button B: X+5, Y+5
+Prize: X=0, Y=0",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+        assert_eq!(
+            Some(vec![0, 2000000000000]),
+            game.find_cheapest_presses(10000000000000, None)
+        );
+
+        let game = Game::parse(
+            "\
+Button A: X+3, Y+3
+Button B: X+5, Y+5
+Prize: X=49, Y=49",
+            &DEFAULT_COSTS,
+        )
+        .unwrap();
+        assert_eq!(Some(vec![3, 8]), game.find_cheapest_presses(0, Some(100)));
+    }
+
+    #[test]
+    fn custom_costs() {
+        let content = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450";
+
+        let result = get_result(content, &[1, 3], 0, Some(100));
+
+        assert_eq!(Ok(200 + 296), result);
+    }
+
+    #[test]
+    fn three_buttons() {
+        let content = "\
+Button A: X+2, Y+0
+Button B: X+0, Y+2
+Button C: X+1, Y+1
+Prize: X=4, Y=4
+
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400";
+
+        let games = parse_content(content, &[1, 1, 3]).unwrap();
+        assert_eq!(2, games.len());
+        assert_eq!(3, games[0].buttons.len());
+        assert_eq!(2, games[1].buttons.len());
+        assert_eq!(Some(vec![2, 2, 0]), games[0].find_cheapest_presses(0, None));
+        assert_eq!(Some(4), games[0].find_cheapest_result(0, None));
+
+        let games = parse_content(content, &[3, 3, 1]).unwrap();
+        assert_eq!(Some(vec![0, 0, 4]), games[0].find_cheapest_presses(0, None));
+        assert_eq!(Some(4), games[0].find_cheapest_result(0, None));
+        assert_eq!(Some(vec![80, 40]), games[1].find_cheapest_presses(0, None));
+
+        // the puzzle only prices A and B
+        assert_eq!(
+            Err(MissingCost { button: 2 }),
+            get_result(content, &DEFAULT_COSTS, 0, None)
+        );
+    }
+
+    #[test]
+    fn four_buttons_press_limit() {
+        let game = Game::parse(
+            "\
+Button A: X+5, Y+5
+Button B: X+3, Y+0
+Button C: X+0, Y+3
+Button D: X+1, Y+1
+Prize: X=13, Y=10",
+            &[1, 2, 2, 10],
+        )
+        .unwrap();
+
+        assert_eq!(Some(vec![2, 1, 0, 0]), game.find_cheapest_presses(0, None));
+        assert_eq!(Some(4), game.find_cheapest_result(0, None));
+        assert_eq!(None, game.find_cheapest_presses(0, Some(1)));
+    }
+
+    #[test]
+    fn many_buttons_far_away() {
+        // an odd distance needs the diagonal button, everything else is cheaper in pairs
+        let content = "\
+Button A: X+2, Y+0
+Button B: X+0, Y+2
+Button C: X+1, Y+1
+Prize: X=5, Y=5";
+
+        let games = parse_content(content, &[1, 1, 3]).unwrap();
+        assert_eq!(
+            Some(vec![5000000000002, 5000000000002, 1]),
+            games[0].find_cheapest_presses(10000000000000, None)
+        );
+        assert_eq!(
+            Ok(10000000000007),
+            get_result(content, &[1, 1, 3], 10000000000000, None)
+        );
+
+        // a longer diagonal step saves one more pair
+        let game = Game::parse(
+            "\
+Button A: X+2, Y+0
+Button B: X+0, Y+2
+Button C: X+1, Y+1
+Button D: X+3, Y+3
+Prize: X=5, Y=5",
+            &[1, 1, 3, 4],
+        )
+        .unwrap();
+        assert_eq!(
+            Some(vec![5000000000001, 5000000000001, 0, 1]),
+            game.find_cheapest_presses(10000000000000, None)
+        );
+        assert_eq!(None, game.find_cheapest_presses(10000000000000, Some(100)));
+    }
+
+    #[test]
+    fn many_puzzle_buttons_far_away() {
+        let content = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Button C: X+57, Y+11
+Button D: X+13, Y+88
+Prize: X=8400, Y=5400";
+
+        let games = parse_content(content, &[3, 1, 2, 2]).unwrap();
+        assert_eq!(
+            Some(vec![81081081163, 108108108134, 0, 10]),
+            games[0].find_cheapest_presses(10000000000000, None)
+        );
+        assert_eq!(
+            Ok(351351351643),
+            get_result(content, &[3, 1, 2, 2], 10000000000000, None)
+        );
+
+        let games = parse_content(content, &[5, 4, 2, 3]).unwrap();
+        assert_eq!(
+            Some(vec![52, 1, 153909296175, 94397701655]),
+            games[0].find_cheapest_presses(10000000000000, None)
+        );
+    }
+
+    #[test]
+    fn buttons_that_dont_move() {
+        let game = Game::parse(
+            "\
+Button A: X+0, Y+0
+Button B: X+94, Y+34
+Button C: X+22, Y+67
+Prize: X=8400, Y=5400",
+            &[1, 3, 1],
+        )
+        .unwrap();
+        assert_eq!(Some(vec![0, 80, 40]), game.find_cheapest_presses(0, None));
 
-        assert_eq!(480, result);
+        let game = Game::parse(
+            "\
+Button A: X+0, Y+0
+Button B: X+0, Y+0
+Button C: X+0, Y+0
+Prize: X=10, Y=10",
+            &[1, 1, 1],
+        )
+        .unwrap();
+        assert_eq!(None, game.find_cheapest_presses(0, None));
+        assert_eq!(Some(vec![0, 0, 0]), game.find_cheapest_presses(-10, None));
     }
 
     #[test]
@@ -165,7 +1053,7 @@ Prize: X=18641, Y=10279";
         let result1 = solve_puzzle_1();
         let result2 = solve_puzzle_2();
 
-        assert_eq!(38714, result1);
-        assert_eq!(74015623345775, result2);
+        assert_eq!(Ok(38714), result1);
+        assert_eq!(Ok(74015623345775), result2);
     }
 }
@@ -1,8 +1,10 @@
+use std::cmp::Ordering;
+
 pub mod puzzle1;
 pub mod puzzle2;
 
-fn split_contents(contents: String) -> Vec<Vec<i64>> {
-    let mut lines: Vec<Vec<i64>> = Vec::new();
+fn split_contents(contents: String, policy: &SafetyPolicy) -> Vec<Report> {
+    let mut reports: Vec<Report> = Vec::new();
 
     for line in contents.lines() {
         let values: Vec<i64> = line
@@ -10,8 +12,348 @@ fn split_contents(contents: String) -> Vec<Vec<i64>> {
             .map(|v| v.parse::<i64>().expect("all values should be ints"))
             .collect();
 
-        lines.push(values);
+        reports.push(Report::new(values, policy));
+    }
+
+    reports
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    Either,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SafetyPolicy {
+    pub min_step: i64,
+    pub max_step: i64,
+    pub trend: Trend,
+    // whether two levels in a row can be the same, wherever the rest are going
+    pub plateaus: bool,
+    pub max_removals: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Unsafety {
+    Plateau,
+    // going against the policy's trend or the way the earlier levels went
+    WrongDirection,
+    StepTooSmall,
+    StepTooLarge,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    // the levels the problem dampener removed, if any
+    Safe { removed: Vec<usize> },
+    // the first level that breaks the policy when nothing is removed, and why
+    Unsafe { index: usize, reason: Unsafety },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Report {
+    pub levels: Vec<i64>,
+    pub verdict: Verdict,
+}
+
+impl Report {
+    pub fn new(levels: Vec<i64>, policy: &SafetyPolicy) -> Self {
+        let verdict = match policy.levels_to_remove(&levels) {
+            Some(removed) => Verdict::Safe { removed },
+            None => {
+                let (index, reason) = policy
+                    .first_problem(&levels)
+                    .expect("a report that isn't safe has a problem");
+                Verdict::Unsafe { index, reason }
+            }
+        };
+
+        Self { levels, verdict }
+    }
+
+    pub fn is_safe(&self) -> bool {
+        matches!(self.verdict, Verdict::Safe { .. })
+    }
+}
+
+impl SafetyPolicy {
+    // the rules from the puzzle, with no problem dampener
+    pub fn standard() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            trend: Trend::Either,
+            plateaus: false,
+            max_removals: 0,
+        }
+    }
+
+    fn orders(&self) -> Vec<Ordering> {
+        match self.trend {
+            Trend::Increasing => vec![Ordering::Less],
+            Trend::Decreasing => vec![Ordering::Greater],
+            Trend::Either => vec![Ordering::Less, Ordering::Greater],
+        }
+    }
+
+    fn check_step(&self, from: i64, to: i64, order: Ordering) -> Result<(), Unsafety> {
+        let step = (from - to).abs();
+        if step == 0 {
+            return if self.plateaus {
+                Ok(())
+            } else {
+                Err(Unsafety::Plateau)
+            };
+        }
+
+        if from.cmp(&to) != order {
+            Err(Unsafety::WrongDirection)
+        } else if step < self.min_step {
+            Err(Unsafety::StepTooSmall)
+        } else if step > self.max_step {
+            Err(Unsafety::StepTooLarge)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn first_problem(&self, levels: &[i64]) -> Option<(usize, Unsafety)> {
+        // with either trend, the first level that changes decides which way it goes
+        let mut order = match self.trend {
+            Trend::Increasing => Some(Ordering::Less),
+            Trend::Decreasing => Some(Ordering::Greater),
+            Trend::Either => None,
+        };
+
+        for i in 1..levels.len() {
+            let (from, to) = (levels[i - 1], levels[i]);
+            let step_order = order.unwrap_or(from.cmp(&to));
+            if let Err(reason) = self.check_step(from, to, step_order) {
+                return Some((i, reason));
+            }
+            if from != to {
+                order = Some(step_order);
+            }
+        }
+
+        None
+    }
+
+    // the fewest level indices to remove so the report is safe, or None when it needs more
+    // than max_removals. Only looks back max_removals + 1 levels from each level, so it's
+    // linear in the length of the report for a fixed number of removals
+    pub fn levels_to_remove(&self, levels: &[i64]) -> Option<Vec<usize>> {
+        self.orders()
+            .into_iter()
+            .filter_map(|order| self.levels_to_remove_in_order(levels, order))
+            .min_by_key(|removed| removed.len())
     }
 
-    lines
+    fn levels_to_remove_in_order(&self, levels: &[i64], order: Ordering) -> Option<Vec<usize>> {
+        let max_removals = self.max_removals;
+        let len = levels.len();
+        if len == 0 {
+            return Some(vec![]);
+        }
+
+        // previous[i][r] is set when level i can be kept with r levels removed before it,
+        // and holds the kept level before it, None when i is the first level kept
+        let mut previous: Vec<Vec<Option<Option<usize>>>> = vec![vec![None; max_removals + 1]; len];
+        for i in 0..len {
+            if i <= max_removals {
+                previous[i][i] = Some(None);
+            }
+
+            // nearest first, so the fewest levels are skipped to get here
+            for j in (i.saturating_sub(max_removals + 1)..i).rev() {
+                if self.check_step(levels[j], levels[i], order).is_err() {
+                    continue;
+                }
+
+                let skipped = i - j - 1;
+                for removed in skipped..=max_removals {
+                    if previous[i][removed].is_none() && previous[j][removed - skipped].is_some() {
+                        previous[i][removed] = Some(Some(j));
+                    }
+                }
+            }
+        }
+
+        // the last level kept, with everything after it removed as well
+        let (mut i, mut removed) = (0..len)
+            .rev()
+            .take(max_removals + 1)
+            .flat_map(|i| (0..=max_removals - (len - 1 - i)).map(move |removed| (i, removed)))
+            .filter(|(i, removed)| previous[*i][*removed].is_some())
+            .min_by_key(|(i, removed)| removed + len - 1 - i)?;
+
+        let mut kept = vec![false; len];
+        loop {
+            kept[i] = true;
+            match previous[i][removed].expect("only reachable levels are followed") {
+                Some(j) => {
+                    removed -= i - j - 1;
+                    i = j;
+                }
+                None => break,
+            }
+        }
+
+        Some((0..len).filter(|i| !kept[*i]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels_to_remove(levels: &[i64], max_removals: usize) -> Option<Vec<usize>> {
+        SafetyPolicy {
+            max_removals,
+            ..SafetyPolicy::standard()
+        }
+        .levels_to_remove(levels)
+    }
+
+    #[test]
+    fn already_safe() {
+        assert_eq!(Some(vec![]), levels_to_remove(&[7, 6, 4, 2, 1], 0));
+        assert_eq!(Some(vec![]), levels_to_remove(&[1, 3, 6, 7, 9], 1));
+        assert_eq!(Some(vec![]), levels_to_remove(&[], 0));
+        assert_eq!(Some(vec![]), levels_to_remove(&[4], 0));
+        assert_eq!(None, levels_to_remove(&[1, 3, 2, 4, 5], 0));
+    }
+
+    #[test]
+    fn one_removal() {
+        assert_eq!(Some(vec![1]), levels_to_remove(&[1, 3, 2, 4, 5], 1));
+        assert_eq!(Some(vec![2]), levels_to_remove(&[8, 6, 4, 4, 1], 1));
+        assert_eq!(Some(vec![4]), levels_to_remove(&[1, 2, 3, 4, 3], 1));
+        assert_eq!(None, levels_to_remove(&[1, 2, 7, 8, 9], 1));
+        assert_eq!(None, levels_to_remove(&[9, 7, 6, 2, 1], 1));
+    }
+
+    #[test]
+    fn removing_the_first_level() {
+        // the first step sets the wrong direction
+        assert_eq!(Some(vec![0]), levels_to_remove(&[5, 6, 4, 3, 2], 1));
+        assert_eq!(Some(vec![0]), levels_to_remove(&[3, 1, 2, 3, 4], 1));
+        assert_eq!(Some(vec![0]), levels_to_remove(&[2, 1, 2, 3], 1));
+
+        // the first step is too big or flat
+        assert_eq!(Some(vec![0]), levels_to_remove(&[1, 5, 6, 7], 1));
+        assert_eq!(Some(vec![0]), levels_to_remove(&[5, 5, 6, 7], 1));
+
+        // removing the second level instead would also fix the direction, but not the step
+        assert_eq!(Some(vec![0]), levels_to_remove(&[10, 4, 5, 6], 1));
+    }
+
+    #[test]
+    fn more_removals() {
+        assert_eq!(None, levels_to_remove(&[1, 2, 1, 2], 1));
+        assert_eq!(Some(vec![0, 1]), levels_to_remove(&[1, 2, 7, 8, 9], 2));
+        assert_eq!(Some(vec![1, 2]), levels_to_remove(&[1, 9, 9, 2, 3], 2));
+        assert_eq!(Some(vec![3, 4]), levels_to_remove(&[1, 2, 3, 9, 9], 3));
+
+        // with enough removals anything is safe
+        assert_eq!(Some(vec![0, 1, 2]), levels_to_remove(&[1, 10, 20, 30], 3));
+    }
+
+    #[test]
+    fn unsafe_reasons() {
+        let policy = SafetyPolicy::standard();
+        let verdict = |levels: Vec<i64>| Report::new(levels, &policy).verdict;
+
+        assert_eq!(
+            Verdict::Unsafe {
+                index: 2,
+                reason: Unsafety::StepTooLarge
+            },
+            verdict(vec![1, 2, 7, 8, 9])
+        );
+        assert_eq!(
+            Verdict::Unsafe {
+                index: 2,
+                reason: Unsafety::WrongDirection
+            },
+            verdict(vec![1, 3, 2, 4, 5])
+        );
+        assert_eq!(
+            Verdict::Unsafe {
+                index: 3,
+                reason: Unsafety::Plateau
+            },
+            verdict(vec![8, 6, 4, 4, 1])
+        );
+        assert_eq!(
+            Verdict::Safe { removed: vec![] },
+            verdict(vec![7, 6, 4, 2, 1])
+        );
+
+        // the problem is always the one before anything is removed
+        let dampened = SafetyPolicy {
+            max_removals: 1,
+            ..policy
+        };
+        assert_eq!(
+            Verdict::Safe { removed: vec![1] },
+            Report::new(vec![1, 3, 2, 4, 5], &dampened).verdict
+        );
+        assert_eq!(
+            Verdict::Unsafe {
+                index: 2,
+                reason: Unsafety::WrongDirection
+            },
+            Report::new(vec![1, 2, 1, 2], &dampened).verdict
+        );
+    }
+
+    #[test]
+    fn policies() {
+        let increasing = SafetyPolicy {
+            trend: Trend::Increasing,
+            ..SafetyPolicy::standard()
+        };
+        assert!(Report::new(vec![1, 2, 4], &increasing).is_safe());
+        assert_eq!(
+            Verdict::Unsafe {
+                index: 1,
+                reason: Unsafety::WrongDirection
+            },
+            Report::new(vec![4, 2, 1], &increasing).verdict
+        );
+
+        let plateaus = SafetyPolicy {
+            plateaus: true,
+            ..SafetyPolicy::standard()
+        };
+        assert!(Report::new(vec![4, 4, 2, 2, 1], &plateaus).is_safe());
+        assert!(!Report::new(vec![4, 4, 2, 3], &plateaus).is_safe());
+
+        let big_steps = SafetyPolicy {
+            min_step: 2,
+            max_step: 5,
+            trend: Trend::Decreasing,
+            ..SafetyPolicy::standard()
+        };
+        assert!(Report::new(vec![20, 15, 13, 8], &big_steps).is_safe());
+        assert_eq!(
+            Verdict::Unsafe {
+                index: 2,
+                reason: Unsafety::StepTooSmall
+            },
+            Report::new(vec![20, 15, 14, 8], &big_steps).verdict
+        );
+
+        let dampened = SafetyPolicy {
+            max_removals: 1,
+            ..big_steps
+        };
+        assert_eq!(
+            Verdict::Safe { removed: vec![2] },
+            Report::new(vec![20, 15, 14, 13], &dampened).verdict
+        );
+    }
 }
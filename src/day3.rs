@@ -1,15 +1,404 @@
-use regex::Regex;
+use std::io::{self, Read};
 
 pub mod puzzle1;
 pub mod puzzle2;
 
-fn get_mul_result(mul: &str) -> i64 {
-    let nums_re = Regex::new(r"\d*,\d*").expect("the regex should parse");
-    let nums = nums_re
-        .find(mul)
-        .expect("comma separated values should always be present");
-    let splits: Vec<&str> = nums.as_str().split(',').collect();
-    let val1: i64 = splits[0].parse().expect("value1 should always be a number");
-    let val2: i64 = splits[1].parse().expect("value2 should always be a number");
-    val1 * val2
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// operands are never longer than this, which keeps the tokens the lexer has to hold on to
+// short
+const MAX_DIGITS: usize = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
+
+// how an instruction is written, name(operand,operand,...)
+struct Spec {
+    name: &'static str,
+    operands: usize,
+    build: fn(&[i64]) -> Instruction,
+}
+
+// every instruction the lexer knows about, a new one only needs an entry here
+const INSTRUCTIONS: [Spec; 3] = [
+    Spec {
+        name: "mul",
+        operands: 2,
+        build: |operands| Instruction::Mul(operands[0], operands[1]),
+    },
+    Spec {
+        name: "do",
+        operands: 0,
+        build: |_| Instruction::Do,
+    },
+    Spec {
+        name: "don't",
+        operands: 0,
+        build: |_| Instruction::Dont,
+    },
+];
+
+const MAX_OPERANDS: usize = max_operands();
+const MAX_TOKEN: usize = max_token();
+const STARTS: [bool; 256] = starts();
+
+const fn max_operands() -> usize {
+    let mut most = 0;
+    let mut i = 0;
+    while i < INSTRUCTIONS.len() {
+        if INSTRUCTIONS[i].operands > most {
+            most = INSTRUCTIONS[i].operands;
+        }
+        i += 1;
+    }
+
+    most
+}
+
+// the name, the brackets, the operands at their longest and the commas between them
+const fn max_token() -> usize {
+    let mut longest = 0;
+    let mut i = 0;
+    while i < INSTRUCTIONS.len() {
+        let spec = &INSTRUCTIONS[i];
+        let len =
+            spec.name.len() + 2 + spec.operands * MAX_DIGITS + spec.operands.saturating_sub(1);
+        if len > longest {
+            longest = len;
+        }
+        i += 1;
+    }
+
+    longest
+}
+
+// the bytes an instruction can start with
+const fn starts() -> [bool; 256] {
+    let mut starts = [false; 256];
+    let mut i = 0;
+    while i < INSTRUCTIONS.len() {
+        starts[INSTRUCTIONS[i].name.as_bytes()[0] as usize] = true;
+        i += 1;
+    }
+
+    starts
+}
+
+enum Scan {
+    Complete(Instruction),
+    // could still become an instruction with more bytes
+    Partial,
+    Invalid,
+}
+
+impl Spec {
+    fn scan(&self, bytes: &[u8]) -> Scan {
+        let name = self.name.as_bytes();
+
+        for (i, b) in bytes.iter().take(name.len() + 1).enumerate() {
+            let expected = name.get(i).copied().unwrap_or(b'(');
+            if *b != expected {
+                return Scan::Invalid;
+            }
+        }
+
+        let mut operands = [0_i64; MAX_OPERANDS];
+        let mut count = 0;
+        let mut digits = 0;
+        for b in bytes.iter().skip(name.len() + 1) {
+            match b {
+                b'0'..=b'9' if digits < MAX_DIGITS && count < self.operands => {
+                    digits += 1;
+                    operands[count] = operands[count] * 10 + (b - b'0') as i64;
+                }
+                b',' if digits > 0 && count + 1 < self.operands => {
+                    count += 1;
+                    digits = 0;
+                }
+                b')' => {
+                    if digits > 0 {
+                        count += 1;
+                    }
+                    return if count == self.operands && (digits > 0 || self.operands == 0) {
+                        Scan::Complete((self.build)(&operands[..count]))
+                    } else {
+                        Scan::Invalid
+                    };
+                }
+                _ => return Scan::Invalid,
+            }
+        }
+
+        Scan::Partial
+    }
+}
+
+// finds instructions one byte at a time, so the memory can arrive in pieces of any size
+pub struct Lexer {
+    // the bytes of a token that might still be completed
+    pending: [u8; MAX_TOKEN],
+    pending_len: usize,
+    // where the pending bytes start in the memory
+    offset: usize,
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self {
+            pending: [0; MAX_TOKEN],
+            pending_len: 0,
+            offset: 0,
+        }
+    }
+}
+
+impl Lexer {
+    // the instruction and its byte offset when this byte completes one
+    pub fn push(&mut self, byte: u8) -> Option<(usize, Instruction)> {
+        // most of the memory is noise, so skip anything that can't start an instruction
+        // without scanning
+        if self.pending_len == 0 && !STARTS[byte as usize] {
+            self.offset += 1;
+            return None;
+        }
+
+        self.pending[self.pending_len] = byte;
+        self.pending_len += 1;
+
+        while self.pending_len > 0 {
+            let pending = &self.pending[..self.pending_len];
+            let mut partial = false;
+            for spec in &INSTRUCTIONS {
+                match spec.scan(pending) {
+                    Scan::Complete(instruction) => {
+                        let offset = self.offset;
+                        self.offset += self.pending_len;
+                        self.pending_len = 0;
+                        return Some((offset, instruction));
+                    }
+                    Scan::Partial => partial = true,
+                    Scan::Invalid => {}
+                }
+            }
+            if partial {
+                return None;
+            }
+
+            // nothing starts here, but something might start on one of the later bytes
+            self.pending.copy_within(1..self.pending_len, 0);
+            self.pending_len -= 1;
+            self.offset += 1;
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Executed {
+    pub offset: usize,
+    pub instruction: Instruction,
+}
+
+pub struct Interpreter {
+    // whether do() and don't() switch the muls on and off
+    conditionals: bool,
+    enabled: bool,
+    pub sum: i64,
+}
+
+impl Interpreter {
+    pub fn new(conditionals: bool) -> Self {
+        Self {
+            conditionals,
+            enabled: true,
+            sum: 0,
+        }
+    }
+
+    // false when the instruction is skipped, a disabled mul or a toggle that's ignored
+    pub fn execute(&mut self, instruction: Instruction) -> bool {
+        match instruction {
+            Instruction::Mul(left, right) if self.enabled => self.sum += left * right,
+            Instruction::Do if self.conditionals => self.enabled = true,
+            Instruction::Dont if self.conditionals => self.enabled = false,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+pub struct Run {
+    pub sum: i64,
+    pub executed: Vec<Executed>,
+}
+
+pub fn run(memory: &str, conditionals: bool) -> Run {
+    let mut executed = vec![];
+    let sum = run_reader(memory.as_bytes(), conditionals, |e| executed.push(e))
+        .expect("reading from a str can't fail");
+
+    Run { sum, executed }
+}
+
+// reads the memory a chunk at a time, only ever holding the chunk and one unfinished token,
+// so the input can be far bigger than fits in memory. Gives the sum of the executed muls
+pub fn run_reader(
+    mut reader: impl Read,
+    conditionals: bool,
+    mut on_executed: impl FnMut(Executed),
+) -> io::Result<i64> {
+    let mut lexer = Lexer::default();
+    let mut interpreter = Interpreter::new(conditionals);
+    let mut buffer = [0_u8; CHUNK_SIZE];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for byte in &buffer[..read] {
+            if let Some((offset, instruction)) = lexer.push(*byte) {
+                if interpreter.execute(instruction) {
+                    on_executed(Executed {
+                        offset,
+                        instruction,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(interpreter.sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn executed_instructions() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        let result = run(memory, true);
+        assert_eq!(48, result.sum);
+        assert_eq!(
+            vec![
+                Executed {
+                    offset: 1,
+                    instruction: Instruction::Mul(2, 4)
+                },
+                Executed {
+                    offset: 20,
+                    instruction: Instruction::Dont
+                },
+                Executed {
+                    offset: 59,
+                    instruction: Instruction::Do
+                },
+                Executed {
+                    offset: 64,
+                    instruction: Instruction::Mul(8, 5)
+                },
+            ],
+            result.executed
+        );
+
+        let result = run(memory, false);
+        assert_eq!(161, result.sum);
+        assert_eq!(4, result.executed.len());
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(0, run("mul(,)mul(1,)mul(,1)mul()", false).sum);
+        assert_eq!(0, run("mul(1234,5)mul(5,1234)mul(1,2,3)", false).sum);
+        assert_eq!(0, run("mul( 1,2)mul(1, 2)mul(-1,2)", false).sum);
+        assert_eq!(492, run("mul(123,4)", false).sum);
+        assert_eq!(0, run("do(1)don't(1)mul(1,1", false).sum);
+    }
+
+    #[test]
+    fn overlapping_tokens() {
+        // a failed token can hide the start of a real one
+        let result = run("mumul(2,3)mul(mul(4,5)", false);
+        assert_eq!(
+            vec![
+                Executed {
+                    offset: 2,
+                    instruction: Instruction::Mul(2, 3)
+                },
+                Executed {
+                    offset: 14,
+                    instruction: Instruction::Mul(4, 5)
+                },
+            ],
+            result.executed
+        );
+
+        assert_eq!(0, run("don'tdo()don't()mul(2,2)", true).sum);
+        assert_eq!(4, run("dodon't(do()mul(2,2)", true).sum);
+
+        // offsets are in bytes, not chars
+        assert_eq!(2, run("émul(1,1)", false).executed[0].offset);
+    }
+
+    // hands out the memory a few bytes at a time, so tokens get split across reads
+    struct Chunked<'a> {
+        bytes: &'a [u8],
+        size: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.size.min(buf.len()).min(self.bytes.len());
+            buf[..read].copy_from_slice(&self.bytes[..read]);
+            self.bytes = &self.bytes[read..];
+            Ok(read)
+        }
+    }
+
+    #[test]
+    fn chunk_boundaries() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))mumul(123,456)";
+        let expected = run(memory, true);
+
+        for size in 1..=memory.len() {
+            let reader = Chunked {
+                bytes: memory.as_bytes(),
+                size,
+            };
+            let mut executed = vec![];
+            let sum = run_reader(reader, true, |e| executed.push(e)).unwrap();
+
+            assert_eq!(expected.sum, sum, "chunks of {size}");
+            assert_eq!(expected.executed, executed, "chunks of {size}");
+        }
+
+        let reader = io::BufReader::new(memory.as_bytes());
+        assert_eq!(161 + 123 * 456, run_reader(reader, false, |_| {}).unwrap());
+    }
+
+    #[test]
+    fn bounded_state() {
+        // nothing here is ever an instruction, but most of it looks like the start of one
+        let memory = "mul(123,45mul(1,do(don't(mul(999,999".repeat(1000);
+        let mut lexer = Lexer::default();
+        for byte in memory.bytes() {
+            assert_eq!(None, lexer.push(byte));
+        }
+
+        // only the unfinished mul at the very end is still held
+        assert_eq!(b"mul(999,999", &lexer.pending[..lexer.pending_len]);
+        assert_eq!(memory.len() - lexer.pending_len, lexer.offset);
+        assert_eq!("mul(123,123)".len(), MAX_TOKEN);
+    }
 }
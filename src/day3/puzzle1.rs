@@ -1,26 +1,15 @@
-use regex::Regex;
+use std::io::Read;
 
-use crate::read_file;
-
-use super::get_mul_result;
+use crate::{day3::run_reader, open_file};
 
 pub fn solve_day3_puzzle1() -> i64 {
-    let contents = read_file("day3.txt");
+    let file = open_file("day3.txt");
 
-    get_result(contents.as_str())
+    get_result(file)
 }
 
-fn get_result(contents: &str) -> i64 {
-    let mut sum = 0_i64;
-    let mul_re = Regex::new(r"mul\(\d*,\d*\)").expect("the regex should parse");
-
-    let muls: Vec<&str> = mul_re.find_iter(contents).map(|m| m.as_str()).collect();
-
-    for mul in muls {
-        sum += get_mul_result(mul);
-    }
-
-    sum
+fn get_result(memory: impl Read) -> i64 {
+    run_reader(memory, false, |_| {}).expect("should be able to read the day 3 memory")
 }
 
 #[cfg(test)]
@@ -31,7 +20,7 @@ mod tests {
     fn demo() {
         let contents = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
 
-        let result = get_result(contents);
+        let result = get_result(contents.as_bytes());
 
         assert_eq!(161, result);
     }
@@ -1,2 +1,432 @@
+use std::collections::HashMap;
+
 pub mod puzzle1;
 pub mod puzzle2;
+
+// the letter in a pattern that matches any letter in the grid
+const ANY: char = '.';
+
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+pub struct WordSearch {
+    grid: Vec<Vec<char>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+        Direction::Up,
+        Direction::UpRight,
+    ];
+
+    // (row, column) change for one step
+    fn offset(&self) -> (i64, i64) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+}
+
+// letters at offsets from the pattern's origin, every other cell can hold anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    cells: Vec<((i64, i64), char)>,
+}
+
+impl Pattern {
+    // a word read from the origin in one direction
+    pub fn word(word: &str, direction: Direction) -> Self {
+        let (dx, dy) = direction.offset();
+        let cells = word
+            .chars()
+            .enumerate()
+            .map(|(i, c)| ((dx * i as i64, dy * i as i64), c))
+            .collect();
+
+        Self { cells }
+    }
+
+    // a stencil drawn as rows of letters, with . for the cells that don't matter, e.g.
+    // M.S
+    // .A.
+    // M.S
+    pub fn parse(stencil: &str) -> Self {
+        let cells = stencil
+            .lines()
+            .enumerate()
+            .flat_map(|(x, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c != ANY)
+                    .map(move |(y, c)| ((x as i64, y as i64), c))
+            })
+            .collect();
+
+        Self { cells }.normalised()
+    }
+
+    // turned a quarter clockwise
+    pub fn rotated(&self) -> Self {
+        let cells = self
+            .cells
+            .iter()
+            .map(|((x, y), c)| ((*y, -x), *c))
+            .collect();
+
+        Self { cells }.normalised()
+    }
+
+    // every distinct way round the pattern can be, so symmetric patterns aren't counted
+    // more than once at the same place
+    pub fn rotations(&self) -> Vec<Pattern> {
+        let mut rotations: Vec<Pattern> = vec![];
+        let mut pattern = self.clone().normalised();
+        for _ in 0..4 {
+            let next = pattern.rotated();
+            if !rotations.contains(&pattern) {
+                rotations.push(pattern);
+            }
+            pattern = next;
+        }
+
+        rotations
+    }
+
+    // moves the pattern so its top left corner is the origin, with the cells in reading
+    // order so the same shape always compares equal
+    fn normalised(mut self) -> Self {
+        let min_x = self
+            .cells
+            .iter()
+            .map(|((x, _), _)| *x)
+            .min()
+            .unwrap_or_default();
+        let min_y = self
+            .cells
+            .iter()
+            .map(|((_, y), _)| *y)
+            .min()
+            .unwrap_or_default();
+        for ((x, y), _) in self.cells.iter_mut() {
+            *x -= min_x;
+            *y -= min_y;
+        }
+        self.cells.sort();
+
+        self
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WordMatch {
+    pub start: (usize, usize),
+    pub direction: Direction,
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PatternMatch {
+    pub origin: (usize, usize),
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl WordSearch {
+    pub fn parse(content: &str) -> Self {
+        let grid = content.lines().map(|l| l.chars().collect()).collect();
+
+        Self { grid }
+    }
+
+    fn get(&self, x: i64, y: i64) -> Option<char> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        self.grid.get(x as usize)?.get(y as usize).copied()
+    }
+
+    fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.grid
+            .iter()
+            .enumerate()
+            .flat_map(|(x, row)| (0..row.len()).map(move |y| (x, y)))
+    }
+
+    // the grid cells the pattern covers with its origin at (x, y), if every letter matches
+    fn matches_at(&self, pattern: &Pattern, (x, y): (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        pattern
+            .cells
+            .iter()
+            .map(|((dx, dy), c)| {
+                let (x, y) = (x as i64 + dx, y as i64 + dy);
+                (self.get(x, y)? == *c).then_some((x as usize, y as usize))
+            })
+            .collect()
+    }
+
+    // a word that reads the same both ways is found once in each direction
+    pub fn find_word(&self, word: &str, directions: &[Direction]) -> Vec<WordMatch> {
+        if word.is_empty() {
+            return vec![];
+        }
+
+        let patterns: Vec<(Direction, Pattern)> = directions
+            .iter()
+            .map(|d| (*d, Pattern::word(word, *d)))
+            .collect();
+
+        let mut matches = vec![];
+        for start in self.positions() {
+            for (direction, pattern) in &patterns {
+                if let Some(cells) = self.matches_at(pattern, start) {
+                    matches.push(WordMatch {
+                        start,
+                        direction: *direction,
+                        cells,
+                    });
+                }
+            }
+        }
+
+        matches
+    }
+
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<PatternMatch> {
+        self.find_patterns(std::slice::from_ref(pattern))
+    }
+
+    // a match for each pattern at each place it fits, e.g. with all the rotations of one
+    pub fn find_patterns(&self, patterns: &[Pattern]) -> Vec<PatternMatch> {
+        let mut matches = vec![];
+        for origin in self.positions() {
+            for pattern in patterns.iter().filter(|p| !p.cells.is_empty()) {
+                if let Some(cells) = self.matches_at(pattern, origin) {
+                    matches.push(PatternMatch { origin, cells });
+                }
+            }
+        }
+
+        matches
+    }
+
+    // the grid with every letter that isn't part of a match replaced by a ., like the
+    // puzzle's examples. With colour on, letters shared by two matches are yellow and
+    // letters shared by more are red
+    pub fn render<'a>(
+        &self,
+        matches: impl IntoIterator<Item = &'a [(usize, usize)]>,
+        colour: bool,
+    ) -> String {
+        let mut uses: HashMap<(usize, usize), usize> = HashMap::new();
+        for cells in matches {
+            for cell in cells {
+                *uses.entry(*cell).or_default() += 1;
+            }
+        }
+
+        let mut lines = vec![];
+        for (x, row) in self.grid.iter().enumerate() {
+            let mut line = String::new();
+            for (y, c) in row.iter().enumerate() {
+                match uses.get(&(x, y)) {
+                    None => line.push(ANY),
+                    Some(1) => line.push(*c),
+                    Some(count) if colour => {
+                        let escape = if *count == 2 { YELLOW } else { RED };
+                        line.push_str(&format!("{escape}{c}{RESET}"));
+                    }
+                    Some(_) => line.push(*c),
+                }
+            }
+            lines.push(line);
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEMO: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[test]
+    fn find_word() {
+        let search = WordSearch::parse(DEMO);
+
+        let matches = search.find_word("XMAS", &[Direction::Right]);
+        assert_eq!(3, matches.len());
+        assert_eq!(
+            WordMatch {
+                start: (0, 5),
+                direction: Direction::Right,
+                cells: vec![(0, 5), (0, 6), (0, 7), (0, 8)],
+            },
+            matches[0]
+        );
+
+        let matches = search.find_word("XMAS", &Direction::ALL);
+        assert_eq!(18, matches.len());
+        assert_eq!(
+            1,
+            matches
+                .iter()
+                .filter(|m| m.direction == Direction::Down)
+                .count()
+        );
+
+        // reading back along a match finds the reversed word in the opposite direction
+        let matches = search.find_word("SAMX", &[Direction::Left]);
+        assert_eq!(3, matches.len());
+        assert_eq!(vec![(0, 8), (0, 7), (0, 6), (0, 5)], matches[0].cells);
+
+        assert!(search.find_word("", &Direction::ALL).is_empty());
+        assert_eq!(
+            search.find_word("X", &[Direction::Up]).len(),
+            DEMO.chars().filter(|c| *c == 'X').count()
+        );
+    }
+
+    #[test]
+    fn find_word_at_edges() {
+        let search = WordSearch::parse("AB\nCD");
+
+        let matches = search.find_word("AD", &Direction::ALL);
+        assert_eq!(1, matches.len());
+        assert_eq!(Direction::DownRight, matches[0].direction);
+
+        let matches = search.find_word("CB", &Direction::ALL);
+        assert_eq!(1, matches.len());
+        assert_eq!((1, 0), matches[0].start);
+        assert_eq!(Direction::UpRight, matches[0].direction);
+
+        assert!(search.find_word("ABC", &Direction::ALL).is_empty());
+    }
+
+    #[test]
+    fn patterns() {
+        let x_mas = Pattern::parse("M.S\n.A.\nM.S");
+        let rotations = x_mas.rotations();
+        assert_eq!(4, rotations.len());
+        assert_eq!(Pattern::parse("M.M\n.A.\nS.S"), rotations[1]);
+
+        let search = WordSearch::parse(DEMO);
+        assert_eq!(9, search.find_patterns(&rotations).len());
+        assert_eq!(
+            PatternMatch {
+                origin: (0, 1),
+                cells: vec![(0, 1), (0, 3), (1, 2), (2, 1), (2, 3)],
+            },
+            search.find_pattern(&x_mas)[0]
+        );
+
+        // a plus looks the same every way round, so only one rotation is kept
+        let plus = Pattern::parse(".M.\nMAM\n.M.");
+        assert_eq!(1, plus.rotations().len());
+        assert_eq!(
+            1,
+            WordSearch::parse("XMX\nMAM\nXMX").find_pattern(&plus).len()
+        );
+
+        let corner = Pattern::parse("XM\nM.");
+        let search = WordSearch::parse("XMMX\nM..M\nM..M\nXMMX");
+        assert_eq!(4, search.find_patterns(&corner.rotations()).len());
+    }
+
+    #[test]
+    fn render() {
+        let search = WordSearch::parse(DEMO);
+        let matches = search.find_word("XMAS", &Direction::ALL);
+
+        let expected = "\
+....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX";
+        let cells = matches.iter().map(|m| m.cells.as_slice());
+        assert_eq!(expected, search.render(cells, false));
+
+        let matches = search.find_patterns(&Pattern::parse("M.S\n.A.\nM.S").rotations());
+        let expected = "\
+.M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........";
+        let cells = matches.iter().map(|m| m.cells.as_slice());
+        assert_eq!(expected, search.render(cells, false));
+    }
+
+    #[test]
+    fn render_overlaps() {
+        let search = WordSearch::parse("XMAS\nMM..\nA.A.\nS..S");
+
+        let matches = search.find_word("XMAS", &[Direction::Right, Direction::Down]);
+        let cells: Vec<&[(usize, usize)]> = matches.iter().map(|m| m.cells.as_slice()).collect();
+        assert_eq!(
+            "XMAS\nM...\nA...\nS...",
+            search.render(cells.clone(), false)
+        );
+        assert_eq!(
+            "\x1b[33mX\x1b[0mMAS\nM...\nA...\nS...",
+            search.render(cells, true)
+        );
+
+        let matches = search.find_word("XMAS", &Direction::ALL);
+        let cells = matches.iter().map(|m| m.cells.as_slice());
+        assert!(search
+            .render(cells, true)
+            .starts_with("\x1b[31mX\x1b[0mMAS\nMM..\n"));
+
+        // nothing found leaves an empty grid
+        assert_eq!("....\n....\n....\n....", search.render([], true));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::read_file;
+use crate::{
+    day4::{Pattern, WordSearch},
+    read_file,
+};
 
 pub fn solve_day4_puzzle2() -> i64 {
     let contents = read_file("day4.txt");
@@ -7,44 +10,16 @@ pub fn solve_day4_puzzle2() -> i64 {
 }
 
 fn get_result(contents: &str) -> i64 {
-    let mut count = 0_i64;
-
-    let mut grid = Vec::<Vec<char>>::new();
-    for line in contents.lines() {
-        grid.push(line.chars().collect::<Vec<char>>());
-    }
-
-    let grid_len = grid.len();
-
-    if grid_len < 3 {
-        return count;
-    }
-
-    let mut x = 1;
-    while x < grid_len - 1 {
-        let mut y = 1;
-        let row_len = grid[x].len();
-        while y < row_len - 1 {
-            let test = grid[x][y];
-            if test == 'A' {
-                let top_left = grid[x - 1][y - 1];
-                let top_right = grid[x - 1][y + 1];
-                let bottom_left = grid[x + 1][y - 1];
-                let bottom_right = grid[x + 1][y + 1];
-
-                let word1: String = [top_left, test, bottom_right].iter().collect();
-                let word2: String = [top_right, test, bottom_left].iter().collect();
-
-                if (word1 == "MAS" || word1 == "SAM") && (word2 == "MAS" || word2 == "SAM") {
-                    count += 1;
-                }
-            }
-            y += 1;
-        }
-        x += 1;
-    }
-
-    count
+    let x_mas = Pattern::parse(
+        "\
+M.S
+.A.
+M.S",
+    );
+
+    WordSearch::parse(contents)
+        .find_patterns(&x_mas.rotations())
+        .len() as i64
 }
 
 #[cfg(test)]
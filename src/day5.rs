@@ -1,30 +1,253 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::read_file;
 
 pub mod puzzle1;
 pub mod puzzle2;
 
-struct RuleSet {
-    before: HashSet<i64>,
+// every X|Y rule, keyed by X
+pub struct RuleSet {
+    before: HashMap<i64, HashSet<i64>>,
+}
+
+// the pages of an update whose rules go round in a circle
+#[derive(Debug, PartialEq)]
+pub struct Cycle {
+    pub pages: Vec<i64>,
+}
+
+// a broken before|after rule and where the two pages are in the update
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    pub before: i64,
+    pub after: i64,
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RuleAnalysis {
+    pub pages_without_rules: Vec<i64>,
+    pub is_total_order: bool,
+    // rules that follow from other rules, with cycles in the rules most of them do
+    pub redundant_rules: Vec<(i64, i64)>,
+}
+
+pub fn analyse_rules() -> RuleAnalysis {
+    let (rules, sections) = parse_content(&read_file("day5.txt"));
+    rules.analyse(&sections)
 }
 
 impl RuleSet {
     fn new() -> RuleSet {
         RuleSet {
-            before: HashSet::new(),
+            before: HashMap::new(),
+        }
+    }
+
+    fn add_rule(&mut self, before: i64, after: i64) {
+        self.before.entry(before).or_default().insert(after);
+    }
+
+    fn must_come_before(&self, page: i64, other: i64) -> bool {
+        self.before
+            .get(&page)
+            .is_some_and(|after| after.contains(&other))
+    }
+
+    // sorts the pages of the section the way order does. The rules alone only order some
+    // pairs of pages, which sort_by can't rely on, so this compares where order put them.
+    // Pages that aren't in the section go first
+    pub fn comparator(&self, section: &[i64]) -> Result<impl Fn(&i64, &i64) -> Ordering, Cycle> {
+        let positions: HashMap<i64, usize> = self
+            .order(section)?
+            .into_iter()
+            .enumerate()
+            .map(|(position, page)| (page, position))
+            .collect();
+
+        Ok(move |page: &i64, other: &i64| positions.get(page).cmp(&positions.get(other)))
+    }
+
+    fn is_ordered(&self, section: &[i64]) -> bool {
+        section.iter().enumerate().all(|(i, page)| {
+            section[i + 1..]
+                .iter()
+                .all(|later| !self.must_come_before(*later, *page))
+        })
+    }
+
+    fn violations(&self, section: &[i64]) -> Vec<Violation> {
+        let mut violations = vec![];
+        for (after_position, after) in section.iter().enumerate() {
+            for (before_position, before) in section.iter().enumerate().skip(after_position + 1) {
+                if self.must_come_before(*before, *after) {
+                    violations.push(Violation {
+                        before: *before,
+                        after: *after,
+                        before_position,
+                        after_position,
+                    });
+                }
+            }
         }
+
+        violations
     }
 
-    fn add_rule(&mut self, before: i64) {
-        self.before.insert(before);
+    fn analyse(&self, sections: &[Vec<i64>]) -> RuleAnalysis {
+        let ruled_pages = self.pages();
+
+        let mut pages_without_rules: Vec<i64> = sections
+            .iter()
+            .flatten()
+            .filter(|page| !ruled_pages.contains(page))
+            .copied()
+            .collect();
+        pages_without_rules.sort();
+        pages_without_rules.dedup();
+
+        // a total order has exactly one way to put every page in order, so each page
+        // has to have a rule with the one after it
+        let mut all_pages: Vec<i64> = ruled_pages.into_iter().collect();
+        all_pages.sort();
+        let is_total_order = match self.order(&all_pages) {
+            Ok(ordered) => ordered
+                .windows(2)
+                .all(|pair| self.must_come_before(pair[0], pair[1])),
+            Err(_) => false,
+        };
+
+        let mut redundant_rules = vec![];
+        for (before, afters) in &self.before {
+            for after in afters {
+                if self.reachable_without(*before, *after) {
+                    redundant_rules.push((*before, *after));
+                }
+            }
+        }
+        redundant_rules.sort();
+
+        RuleAnalysis {
+            pages_without_rules,
+            is_total_order,
+            redundant_rules,
+        }
     }
 
-    fn is_valid_after(&self, value: &i64) -> bool {
-        self.before.contains(value)
+    fn pages(&self) -> HashSet<i64> {
+        self.before
+            .iter()
+            .flat_map(|(before, afters)| std::iter::once(before).chain(afters))
+            .copied()
+            .collect()
+    }
+
+    // whether the rules still put before ahead of after with the before|after rule removed
+    fn reachable_without(&self, before: i64, after: i64) -> bool {
+        let mut visited = HashSet::from([before]);
+        let mut queue: Vec<i64> = self.before[&before]
+            .iter()
+            .filter(|page| **page != after)
+            .copied()
+            .collect();
+
+        while let Some(page) = queue.pop() {
+            if page == after {
+                return true;
+            }
+
+            if !visited.insert(page) {
+                continue;
+            }
+
+            if let Some(afters) = self.before.get(&page) {
+                queue.extend(afters.iter().filter(|p| !visited.contains(p)));
+            }
+        }
+
+        false
+    }
+
+    // Kahn's algorithm over the rules between the pages in the section. Pages the rules
+    // don't order keep their original order
+    fn order(&self, section: &[i64]) -> Result<Vec<i64>, Cycle> {
+        let mut in_degree = vec![0; section.len()];
+        for (i, page) in section.iter().enumerate() {
+            for other in section {
+                if self.must_come_before(*other, *page) {
+                    in_degree[i] += 1;
+                }
+            }
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> = in_degree
+            .iter()
+            .enumerate()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(i, _)| Reverse(i))
+            .collect();
+
+        let mut ordered = Vec::with_capacity(section.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            ordered.push(section[i]);
+            for (j, other) in section.iter().enumerate() {
+                if self.must_come_before(section[i], *other) {
+                    in_degree[j] -= 1;
+                    if in_degree[j] == 0 {
+                        ready.push(Reverse(j));
+                    }
+                }
+            }
+        }
+
+        if ordered.len() < section.len() {
+            // what's left is the cycles and anything stuck after them, only the pages that
+            // lead back to themselves are part of a cycle
+            let left: Vec<i64> = section
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| in_degree[*i] > 0)
+                .map(|(_, page)| *page)
+                .collect();
+            let pages = left
+                .iter()
+                .filter(|page| self.leads_back(**page, &left))
+                .copied()
+                .collect();
+            return Err(Cycle { pages });
+        }
+
+        Ok(ordered)
+    }
+
+    // whether following the rules between the pages from page gets back to it
+    fn leads_back(&self, page: i64, pages: &[i64]) -> bool {
+        let mut seen: HashSet<i64> = HashSet::new();
+        let mut stack = vec![page];
+        while let Some(current) = stack.pop() {
+            for next in pages {
+                if !self.must_come_before(current, *next) {
+                    continue;
+                }
+                if *next == page {
+                    return true;
+                }
+                if seen.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+
+        false
     }
 }
 
-fn parse_content(content: &str) -> (HashMap<i64, RuleSet>, Vec<Vec<i64>>) {
-    let mut rules: HashMap<i64, RuleSet> = HashMap::new();
+fn parse_content(content: &str) -> (RuleSet, Vec<Vec<i64>>) {
+    let mut rules = RuleSet::new();
     let mut sections: Vec<Vec<i64>> = Vec::new();
 
     let mut parse_rules = true;
@@ -43,9 +266,7 @@ fn parse_content(content: &str) -> (HashMap<i64, RuleSet>, Vec<Vec<i64>>) {
 
             let key: i64 = splits[0].parse().expect("rule key must be a number");
             let before: i64 = splits[1].parse().expect("rule value must be a number");
-            let rule = rules.entry(key).or_insert_with(RuleSet::new);
-
-            rule.add_rule(before);
+            rules.add_rule(key, before);
         } else {
             let splits: Vec<i64> = line
                 .split(',')
@@ -13,36 +13,8 @@ fn get_result(content: &str) -> i64 {
     let mut sum = 0;
 
     for section in sections {
-        let section_len = section.len();
-        let mut i = 0;
-        let mut is_valid = true;
-
-        while i < section_len {
-            let value = section[i];
-            let mut j = i + 1;
-
-            while j < section_len {
-                let next_value = section[j];
-                if rules.contains_key(&next_value) {
-                    let rule = &rules[&next_value];
-                    if rule.is_valid_after(&value) {
-                        is_valid = false;
-                        break;
-                    }
-                }
-
-                j += 1;
-            }
-
-            if !is_valid {
-                break;
-            }
-
-            i += 1;
-        }
-
-        if is_valid {
-            let middle_index = section_len / 2;
+        if rules.violations(&section).is_empty() {
+            let middle_index = section.len() / 2;
             let middle = &section[middle_index];
             sum += middle;
         }
@@ -54,6 +26,7 @@ fn get_result(content: &str) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::day5::{RuleAnalysis, Violation};
 
     #[test]
     fn demo() {
@@ -92,6 +65,106 @@ mod tests {
         assert_eq!(143, result);
     }
 
+    #[test]
+    fn violations() {
+        let content = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+        let (rules, sections) = parse_content(content);
+
+        assert!(rules.violations(&sections[0]).is_empty());
+        assert_eq!(
+            vec![Violation {
+                before: 97,
+                after: 75,
+                before_position: 1,
+                after_position: 0,
+            }],
+            rules.violations(&sections[1])
+        );
+        assert_eq!(
+            vec![Violation {
+                before: 29,
+                after: 13,
+                before_position: 2,
+                after_position: 1,
+            }],
+            rules.violations(&sections[2])
+        );
+
+        let broken: Vec<(i64, i64, usize, usize)> = rules
+            .violations(&sections[3])
+            .iter()
+            .map(|v| (v.before, v.after, v.before_position, v.after_position))
+            .collect();
+        assert_eq!(
+            vec![
+                (75, 13, 2, 1),
+                (29, 13, 3, 1),
+                (47, 13, 4, 1),
+                (47, 29, 4, 3)
+            ],
+            broken
+        );
+
+        // the demo rules give every pair of pages, so only the ones next to each other in
+        // 97, 75, 47, 61, 53, 29, 13 aren't implied by the others
+        let analysis = rules.analyse(&sections);
+        assert!(analysis.pages_without_rules.is_empty());
+        assert!(analysis.is_total_order);
+        assert_eq!(15, analysis.redundant_rules.len());
+        assert!(!analysis.redundant_rules.contains(&(97, 75)));
+        assert!(analysis.redundant_rules.contains(&(97, 13)));
+    }
+
+    #[test]
+    fn analyse() {
+        let (rules, sections) = parse_content("1|2\n2|3\n1|3\n\n1,2,3,4\n5,1");
+
+        assert_eq!(
+            RuleAnalysis {
+                pages_without_rules: vec![4, 5],
+                is_total_order: true,
+                redundant_rules: vec![(1, 3)],
+            },
+            rules.analyse(&sections)
+        );
+
+        // 1 and 4 could go either way round
+        let (rules, sections) = parse_content("1|2\n4|2\n\n1,4,2");
+        assert!(!rules.analyse(&sections).is_total_order);
+
+        let (rules, sections) = parse_content("1|2\n2|3\n3|1\n\n1,2,3");
+        let analysis = rules.analyse(&sections);
+        assert!(!analysis.is_total_order);
+        assert!(analysis.redundant_rules.is_empty());
+    }
+
     #[test]
     fn file() {
         dotenvy::dotenv().expect("should be able to load .env file!");
@@ -1,90 +1,35 @@
-use std::collections::HashMap;
-
 use crate::read_file;
 
-use super::{parse_content, RuleSet};
+use super::{parse_content, Cycle};
 
-pub fn solve_day5_puzzle2() -> i64 {
+pub fn solve_day5_puzzle2() -> Result<i64, Cycle> {
     let content = read_file("day5.txt");
     get_result(content.as_str())
 }
 
-fn get_result(content: &str) -> i64 {
+fn get_result(content: &str) -> Result<i64, Cycle> {
     let (rules, sections) = parse_content(content);
 
     let mut sum = 0;
 
     for section in sections {
-        let mut keep_reordering = true;
-        let mut section_to_use = section;
-
-        if let Some(s) = reorder_section(&section_to_use, &rules) {
-            section_to_use = s;
-        } else {
+        if rules.is_ordered(&section) {
             continue;
         }
 
-        while keep_reordering {
-            if let Some(new_section) = reorder_section(&section_to_use, &rules) {
-                section_to_use = new_section;
-            } else {
-                keep_reordering = false;
-            }
-        }
+        let ordered = rules.order(&section)?;
 
-        let middle_index = section_to_use.len() / 2;
-        sum += section_to_use[middle_index];
+        let middle_index = ordered.len() / 2;
+        sum += ordered[middle_index];
     }
 
-    sum
-}
-
-fn reorder_section(section: &[i64], rules: &HashMap<i64, RuleSet>) -> Option<Vec<i64>> {
-    let section_len = section.len();
-    let mut i = 0;
-    let mut is_valid = true;
-
-    while i < section_len {
-        let value = section[i];
-        let mut j = i + 1;
-
-        while j < section_len {
-            let next_value = section[j];
-            if rules.contains_key(&next_value) {
-                let rule = &rules[&next_value];
-                if rule.is_valid_after(&value) {
-                    is_valid = false;
-                    break;
-                }
-            }
-
-            j += 1;
-        }
-
-        if !is_valid {
-            let mut new_section: Vec<i64> = Vec::new();
-            for (x, v) in section.iter().enumerate() {
-                if x == i {
-                    new_section.push(section[j]);
-                } else if x == j {
-                    new_section.push(section[i]);
-                } else {
-                    new_section.push(*v);
-                }
-            }
-
-            return Some(new_section);
-        }
-
-        i += 1;
-    }
-
-    None
+    Ok(sum)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cmp::Ordering;
 
     #[test]
     fn demo() {
@@ -120,7 +65,100 @@ mod tests {
 
         let result = get_result(content);
 
-        assert_eq!(123, result);
+        assert_eq!(Ok(123), result);
+    }
+
+    #[test]
+    fn order() {
+        let content = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+        let (rules, sections) = parse_content(content);
+
+        let expected = vec![
+            vec![97, 75, 47, 61, 53],
+            vec![61, 29, 13],
+            vec![97, 75, 47, 29, 13],
+        ];
+        for (section, expected) in sections.iter().zip(expected) {
+            assert_eq!(Ok(expected.clone()), rules.order(section));
+
+            let compare = rules.comparator(section).unwrap();
+            let mut sorted = section.clone();
+            sorted.sort_by(&compare);
+            assert_eq!(expected, sorted);
+        }
+    }
+
+    #[test]
+    fn unordered_pages_keep_their_place() {
+        let (rules, _) = parse_content("1|2\n");
+
+        assert_eq!(Ok(vec![5, 4, 1, 2, 3]), rules.order(&[5, 2, 4, 1, 3]));
+
+        // the rules don't order 5 and 4, but the comparator still does
+        let section = [5, 2, 4, 1, 3];
+        let compare = rules.comparator(&section).unwrap();
+        let mut sorted = section;
+        sorted.sort_by(&compare);
+        assert_eq!([5, 4, 1, 2, 3], sorted);
+        assert_eq!(Ordering::Less, compare(&5, &4));
+        assert_eq!(Ordering::Less, compare(&7, &5));
+    }
+
+    #[test]
+    fn cycles() {
+        let (rules, _) = parse_content("1|2\n2|3\n3|1\n3|4\n");
+
+        // 4 only comes after the cycle, it isn't part of it
+        assert_eq!(
+            Err(Cycle {
+                pages: vec![3, 2, 1]
+            }),
+            rules.order(&[3, 2, 1, 4])
+        );
+        assert_eq!(Ok(vec![5, 1, 2]), rules.order(&[2, 5, 1]));
+        assert!(rules.comparator(&[3, 2, 1, 4]).is_err());
+
+        // two cycles joined up by a page between them
+        let (rules, _) = parse_content("1|2\n2|1\n2|5\n5|3\n3|4\n4|3\n6|1\n");
+        assert_eq!(
+            Err(Cycle {
+                pages: vec![1, 2, 3, 4]
+            }),
+            rules.order(&[6, 1, 2, 5, 3, 4])
+        );
+
+        assert_eq!(
+            Err(Cycle {
+                pages: vec![3, 1, 2]
+            }),
+            get_result("1|2\n2|3\n3|1\n\n2,1,4\n3,1,2\n")
+        );
     }
 
     #[test]
@@ -129,6 +167,6 @@ mod tests {
 
         let result = solve_day5_puzzle2();
 
-        assert_eq!(4713, result);
+        assert_eq!(Ok(4713), result);
     }
 }
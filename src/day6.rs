@@ -1,15 +1,15 @@
-use std::borrow::BorrowMut;
+use std::collections::HashSet;
 
 pub mod puzzle1;
 pub mod puzzle2;
 
-struct Map {
+pub struct Map {
     tiles: Vec<Vec<Tile>>,
     guard: Guard,
 }
 
 impl Map {
-    fn parse(content: &str) -> Self {
+    pub fn parse(content: &str) -> Self {
         let mut tiles: Vec<Vec<Tile>> = Vec::new();
         let mut guard: Option<Guard> = None;
 
@@ -37,58 +37,194 @@ impl Map {
     }
 
     fn move_guard(&mut self) -> bool {
+        if let Some(next) = self.step(&self.guard, None) {
+            self.guard = next;
+            self.tiles[next.pos_x][next.pos_y].visited = true;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    // moves or turns the guard once without changing the map, None when the guard leaves it
+    fn step(&self, guard: &Guard, extra_obstacle: Option<(usize, usize)>) -> Option<Guard> {
         let exit_x = self.tiles.len() - 1;
-        let exit_y = self.tiles[self.guard.pos_x].len() - 1;
+        let exit_y = self.tiles[guard.pos_x].len() - 1;
 
-        let next_pos: Option<(usize, usize)> = match self.guard.direction {
+        let (next_x, next_y) = match guard.direction {
             Direction::Up => {
-                if self.guard.pos_x == 0 {
+                if guard.pos_x == 0 {
                     None
                 } else {
-                    Some((self.guard.pos_x - 1, self.guard.pos_y))
+                    Some((guard.pos_x - 1, guard.pos_y))
                 }
             }
             Direction::Down => {
-                if self.guard.pos_x == exit_x {
+                if guard.pos_x == exit_x {
                     None
                 } else {
-                    Some((self.guard.pos_x + 1, self.guard.pos_y))
+                    Some((guard.pos_x + 1, guard.pos_y))
                 }
             }
             Direction::Left => {
-                if self.guard.pos_y == 0 {
+                if guard.pos_y == 0 {
                     None
                 } else {
-                    Some((self.guard.pos_x, self.guard.pos_y - 1))
+                    Some((guard.pos_x, guard.pos_y - 1))
                 }
             }
             Direction::Right => {
-                if self.guard.pos_y == exit_y {
+                if guard.pos_y == exit_y {
                     None
                 } else {
-                    Some((self.guard.pos_x, self.guard.pos_y + 1))
+                    Some((guard.pos_x, guard.pos_y + 1))
                 }
             }
-        };
+        }?;
 
-        if let Some((next_x, next_y)) = next_pos {
-            let tile = self.tiles[next_x][next_y].borrow_mut();
-            match tile.tile_type {
-                TileType::Open => {
-                    self.guard.pos_x = next_x;
-                    self.guard.pos_y = next_y;
-                    tile.visited = true;
+        let mut next = *guard;
+        if self.tiles[next_x][next_y].tile_type == TileType::Obstacle
+            || extra_obstacle == Some((next_x, next_y))
+        {
+            next.turn_right();
+        } else {
+            next.pos_x = next_x;
+            next.pos_y = next_y;
+        }
+
+        Some(next)
+    }
+
+    // every cell the guard walks through, stopping once it's back in a position and
+    // direction it's already been in when the map alone keeps it in a loop
+    fn patrol_cells(&self) -> HashSet<(usize, usize)> {
+        let mut guard = self.guard;
+        let mut seen = HashSet::from([guard]);
+        let mut cells = HashSet::from([(guard.pos_x, guard.pos_y)]);
+        while let Some(next) = self.step(&guard, None) {
+            if !seen.insert(next) {
+                break;
+            }
+            cells.insert((next.pos_x, next.pos_y));
+            guard = next;
+        }
+
+        cells
+    }
+
+    fn loops_with_obstacle(&self, obstacle: (usize, usize)) -> bool {
+        let mut guard = self.guard;
+        let mut turns: HashSet<Guard> = HashSet::new();
+        while let Some(next) = self.step(&guard, Some(obstacle)) {
+            // the guard is looping as soon as it makes the same turn in the same place twice
+            if next.direction != guard.direction && !turns.insert(next) {
+                return true;
+            }
+            guard = next;
+        }
+
+        false
+    }
+
+    // every move and turn from the start, the path ends on the first repeated state when
+    // the guard is stuck in a loop
+    pub fn patrol(&self, obstacle: Option<(usize, usize)>) -> Patrol {
+        let mut guard = self.guard;
+        let mut steps = vec![guard];
+        let mut seen = HashSet::from([guard]);
+
+        let end = loop {
+            match self.step(&guard, obstacle) {
+                Some(next) => {
+                    if !seen.insert(next) {
+                        break PatrolEnd::Loop(next);
+                    }
+                    steps.push(next);
+                    guard = next;
                 }
-                TileType::Obstacle => self.guard.turn_right(),
+                None => break PatrolEnd::Exit((guard.pos_x, guard.pos_y)),
             }
+        };
 
-            true
-        } else {
-            false
+        Patrol {
+            steps,
+            end,
+            obstacle,
+        }
+    }
+
+    pub fn looping_obstacles(&self) -> Vec<(usize, usize)> {
+        let start = (self.guard.pos_x, self.guard.pos_y);
+
+        // an obstruction can only change the patrol if the guard would have walked into it
+        let mut candidates: Vec<(usize, usize)> = self.patrol_cells().into_iter().collect();
+        candidates.retain(|c| *c != start);
+        candidates.sort();
+
+        candidates
+            .into_iter()
+            .filter(|obstacle| self.loops_with_obstacle(*obstacle))
+            .collect()
+    }
+
+    // uses the puzzle's notation, | and - for the guard going up and down or left and right,
+    // + where it did both and O for the extra obstacle
+    pub fn render(&self, patrol: &Patrol) -> String {
+        let mut vertical: HashSet<(usize, usize)> = HashSet::new();
+        let mut horizontal: HashSet<(usize, usize)> = HashSet::new();
+        for step in &patrol.steps {
+            let pos = (step.pos_x, step.pos_y);
+            match step.direction {
+                Direction::Up | Direction::Down => vertical.insert(pos),
+                Direction::Left | Direction::Right => horizontal.insert(pos),
+            };
+        }
+
+        let start = (self.guard.pos_x, self.guard.pos_y);
+        let mut lines = vec![];
+        for (x, tile_line) in self.tiles.iter().enumerate() {
+            let mut line = String::new();
+            for (y, tile) in tile_line.iter().enumerate() {
+                let pos = (x, y);
+                let c = if pos == start {
+                    '^'
+                } else if patrol.obstacle == Some(pos) {
+                    'O'
+                } else if tile.tile_type == TileType::Obstacle {
+                    '#'
+                } else {
+                    match (vertical.contains(&pos), horizontal.contains(&pos)) {
+                        (true, true) => '+',
+                        (true, false) => '|',
+                        (false, true) => '-',
+                        (false, false) => '.',
+                    }
+                };
+                line.push(c);
+            }
+            lines.push(line);
         }
+
+        lines.join("\n")
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum PatrolEnd {
+    // the last position before the guard walks off the map
+    Exit((usize, usize)),
+    // the first position and direction the guard finds itself in again
+    Loop(Guard),
+}
+
+#[derive(Debug)]
+pub struct Patrol {
+    pub steps: Vec<Guard>,
+    pub end: PatrolEnd,
+    pub obstacle: Option<(usize, usize)>,
+}
+
 #[derive(PartialEq)]
 enum TileType {
     Open,
@@ -100,17 +236,19 @@ struct Tile {
     visited: bool,
 }
 
-enum Direction {
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub enum Direction {
     Up,
     Down,
     Left,
     Right,
 }
 
-struct Guard {
-    pos_x: usize,
-    pos_y: usize,
-    direction: Direction,
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub struct Guard {
+    pub pos_x: usize,
+    pub pos_y: usize,
+    pub direction: Direction,
 }
 
 impl Guard {
@@ -26,6 +26,7 @@ fn get_result(content: &str) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::day6::{Direction, Guard, PatrolEnd};
 
     #[test]
     fn demo() {
@@ -46,6 +47,56 @@ mod tests {
         assert_eq!(41, result);
     }
 
+    #[test]
+    fn patrol() {
+        let content = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+        let map = Map::parse(content);
+        let patrol = map.patrol(None);
+
+        assert_eq!(PatrolEnd::Exit((9, 7)), patrol.end);
+        assert_eq!(
+            Guard {
+                pos_x: 6,
+                pos_y: 4,
+                direction: Direction::Up,
+            },
+            patrol.steps[0]
+        );
+        assert_eq!(
+            Guard {
+                pos_x: 1,
+                pos_y: 4,
+                direction: Direction::Right,
+            },
+            patrol.steps[6]
+        );
+        assert_eq!(
+            "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+.+----++#.
+#+----+|..
+......#|..",
+            map.render(&patrol)
+        );
+    }
+
     #[test]
     fn file() {
         dotenvy::dotenv().expect("should be able to load .env file!");
@@ -8,45 +8,14 @@ pub fn solve_day6_puzzle2() -> i64 {
 }
 
 fn get_result(content: &str) -> i64 {
-    let mut count = 0;
-    let chars: Vec<char> = content.chars().collect();
-    let char_count = chars.len();
-    for x in 0..char_count {
-        if chars[x] == '#' || chars[x] == '^' || chars[x] == '\n' {
-            continue;
-        }
-        let mut new_content = String::new();
-        for (y, c) in chars.iter().enumerate() {
-            if y == x {
-                new_content.push('#');
-            } else {
-                new_content.push(*c);
-            }
-        }
-
-        let max_iterations = 10000;
-        let mut current_iteration = 0;
-        let mut map = Map::parse(new_content.as_str());
-
-        while map.move_guard() {
-            if current_iteration == max_iterations {
-                break;
-            }
-
-            current_iteration += 1;
-        }
-
-        if current_iteration == max_iterations {
-            count += 1;
-        }
-    }
-
-    count
+    let map = Map::parse(content);
+    map.looping_obstacles().len() as i64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::day6::{Direction, Guard, PatrolEnd};
 
     #[test]
     fn demo() {
@@ -67,6 +36,105 @@ mod tests {
         assert_eq!(6, result);
     }
 
+    #[test]
+    fn looping_obstacles() {
+        let content = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+        let map = Map::parse(content);
+
+        assert_eq!(
+            vec![(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)],
+            map.looping_obstacles()
+        );
+        assert!(!map.loops_with_obstacle((0, 0)));
+    }
+
+    #[test]
+    fn render_loops() {
+        let content = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+        let map = Map::parse(content);
+
+        let patrol = map.patrol(Some((6, 3)));
+        assert_eq!(
+            PatrolEnd::Loop(Guard {
+                pos_x: 6,
+                pos_y: 4,
+                direction: Direction::Up,
+            }),
+            patrol.end
+        );
+        assert_eq!(
+            "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...",
+            map.render(&patrol)
+        );
+
+        let patrol = map.patrol(Some((7, 6)));
+        assert_eq!(
+            "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+......O.#.
+#.........
+......#...",
+            map.render(&patrol)
+        );
+    }
+
+    #[test]
+    fn loop_without_the_obstacle() {
+        // the guard starts inside a box and walks around it forever
+        let content = "\
+.#...
+....#
+.^...
+#....
+...#.";
+
+        let map = Map::parse(content);
+
+        assert!(map.loops_with_obstacle((4, 0)));
+
+        // an obstacle anywhere on the guard's path turns it out of the box and off the map
+        assert_eq!(Vec::<(usize, usize)>::new(), map.looping_obstacles());
+        assert_eq!(0, get_result(content));
+    }
+
     #[test]
     fn file() {
         dotenvy::dotenv().expect("should be able to load .env file!");
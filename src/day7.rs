@@ -1,6 +1,22 @@
+use crate::read_file;
+
 pub mod puzzle1;
 pub mod puzzle2;
 
+pub fn solve_with_operators(operators: &[Operator]) -> i64 {
+    sum_solvable(read_file("day7.txt").as_str(), operators)
+}
+
+fn sum_solvable(content: &str, operators: &[Operator]) -> i64 {
+    let equations = parse_contents(content);
+
+    equations
+        .iter()
+        .filter(|x| x.can_solve(operators))
+        .map(|x| x.test_value)
+        .sum()
+}
+
 #[derive(Debug, Clone)]
 struct Equation {
     test_value: i64,
@@ -8,34 +24,45 @@ struct Equation {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-enum Operation {
-    Add(i64),
-    Multiply(i64),
-    Concat(i64),
+pub enum Operator {
+    Add,
+    Multiply,
+    Concat,
+    Subtract,
+    Divide,
+    Xor,
 }
 
-impl Operation {
-    fn next(&self, concat: bool) -> Self {
-        if concat {
-            return self.next_concat();
-        }
-
+impl Operator {
+    // None when the result doesn't fit in an i64 or isn't defined, which ends that branch
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
         match self {
-            Operation::Add(i) => Operation::Multiply(*i),
-            Operation::Multiply(i) => Operation::Add(*i),
-            Operation::Concat(_) => *self,
+            Operator::Add => left.checked_add(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Concat => {
+                if left < 0 || right < 0 {
+                    return None;
+                }
+                left.checked_mul(concat_shift(right)?)?.checked_add(right)
+            }
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Divide => left.checked_div(right),
+            Operator::Xor => Some(left ^ right),
         }
     }
 
-    fn next_concat(&self) -> Self {
-        match self {
-            Operation::Add(i) => Operation::Concat(*i),
-            Operation::Concat(i) => Operation::Multiply(*i),
-            Operation::Multiply(i) => Operation::Add(*i),
-        }
+    // never makes a positive value smaller when the other value is positive too, which is
+    // what the backwards search relies on. Multiplying by zero is why that needs positive
+    // values
+    fn is_growing(&self) -> bool {
+        matches!(self, Operator::Add | Operator::Multiply | Operator::Concat)
     }
 }
 
+fn concat_shift(value: i64) -> Option<i64> {
+    10_i64.checked_pow(value.checked_ilog10().unwrap_or_default() + 1)
+}
+
 impl Equation {
     fn parse(line: &str) -> Self {
         let colon_split: Vec<&str> = line.split(':').collect();
@@ -55,78 +82,79 @@ impl Equation {
         Self { test_value, values }
     }
 
-    fn can_solve(&self, concat: bool) -> bool {
-        let values_len = self.values.len();
-        let value = self
+    fn can_solve(&self, operators: &[Operator]) -> bool {
+        self.solve(operators).is_some()
+    }
+
+    // the operators to put between the values, from left to right
+    fn solve(&self, operators: &[Operator]) -> Option<Vec<Operator>> {
+        if operators.iter().all(Operator::is_growing) && self.values.iter().all(|v| *v > 0) {
+            return solve_backwards(self.test_value, &self.values, operators);
+        }
+
+        let (first, rest) = self
             .values
-            .first()
+            .split_first()
             .expect("there should always be at least one value");
-        if values_len == 1 {
-            return *value == self.test_value;
-        }
+        solve_forwards(*first, rest, self.test_value, operators)
+    }
+}
 
-        let mut operations: Vec<Operation> = Vec::new();
-        for x in 1..values_len {
-            operations.push(Operation::Add(self.values[x]));
-        }
+fn parse_contents(content: &str) -> Vec<Equation> {
+    content.lines().map(Equation::parse).collect()
+}
 
-        loop {
-            let mut answer = *value;
-            for o in operations.iter() {
-                answer = match o {
-                    Operation::Add(i) => answer + i,
-                    Operation::Multiply(i) => answer * i,
-                    Operation::Concat(i) => {
-                        if !concat {
-                            answer
-                        } else {
-                            let answer_string = answer.to_string() + i.to_string().as_str();
-                            answer_string
-                                .parse::<i64>()
-                                .expect("should always be a number")
-                        }
-                    }
-                }
-            }
+// works from the last value back to the first, undoing each operator only when it could
+// have produced the target, so most branches are cut off straight away
+fn solve_backwards(target: i64, values: &[i64], operators: &[Operator]) -> Option<Vec<Operator>> {
+    let (last, rest) = values
+        .split_last()
+        .expect("there should always be at least one value");
 
-            if answer == self.test_value {
-                return true;
-            }
+    if rest.is_empty() {
+        return (*last == target).then(Vec::new);
+    }
 
-            if all_multiply(&operations) {
-                break;
-            }
+    let mut attempts = vec![];
 
-            let mut value_added = false;
-            for x in 0..operations.len() {
-                match operations[x] {
-                    Operation::Add(_) => {
-                        operations[x] = operations[x].next(concat);
-                        value_added = true;
-                    }
-                    _ => operations[x] = operations[x].next(concat),
-                }
+    if operators.contains(&Operator::Multiply) && target % last == 0 {
+        attempts.push((target / last, Operator::Multiply));
+    }
 
-                if value_added {
-                    break;
-                }
+    if operators.contains(&Operator::Concat) {
+        if let Some(shift) = concat_shift(*last) {
+            if target % shift == *last {
+                attempts.push((target / shift, Operator::Concat));
             }
         }
-        false
     }
-}
-
-fn parse_contents(content: &str) -> Vec<Equation> {
-    content.lines().map(Equation::parse).collect()
-}
 
-fn all_multiply(operations: &[Operation]) -> bool {
-    for o in operations.iter() {
-        match o {
-            Operation::Multiply(_) => (),
-            _ => return false,
-        }
+    if operators.contains(&Operator::Add) && target - last >= 0 {
+        attempts.push((target - last, Operator::Add));
     }
 
-    true
+    attempts.into_iter().find_map(|(target, operator)| {
+        let mut solved = solve_backwards(target, rest, operators)?;
+        solved.push(operator);
+        Some(solved)
+    })
+}
+
+// tries every operator from left to right, for operator sets that can make values smaller
+fn solve_forwards(
+    value: i64,
+    values: &[i64],
+    target: i64,
+    operators: &[Operator],
+) -> Option<Vec<Operator>> {
+    let Some((next, rest)) = values.split_first() else {
+        return (value == target).then(Vec::new);
+    };
+
+    operators.iter().find_map(|operator| {
+        let value = operator.apply(value, *next)?;
+        let mut solved = solve_forwards(value, rest, target, operators)?;
+        solved.insert(0, *operator);
+        Some(solved)
+    })
 }
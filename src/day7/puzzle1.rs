@@ -1,19 +1,13 @@
 use crate::read_file;
 
-use super::parse_contents;
+use super::{sum_solvable, Operator};
 
 pub fn solve_day7_puzzle1() -> i64 {
     get_result(read_file("day7.txt").as_str())
 }
 
 fn get_result(content: &str) -> i64 {
-    let equations = parse_contents(content);
-
-    equations
-        .iter()
-        .filter(|x| x.can_solve(false))
-        .map(|x| x.test_value)
-        .sum()
+    sum_solvable(content, &[Operator::Add, Operator::Multiply])
 }
 
 #[cfg(test)]
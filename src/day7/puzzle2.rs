@@ -1,24 +1,26 @@
 use crate::read_file;
 
-use super::parse_contents;
+use super::{sum_solvable, Operator};
 
 pub fn solve_day7_puzzle2() -> i64 {
     get_result(read_file("day7.txt").as_str())
 }
 
 fn get_result(content: &str) -> i64 {
-    let equations = parse_contents(content);
-
-    equations
-        .iter()
-        .filter(|x| x.can_solve(true))
-        .map(|x| x.test_value)
-        .sum()
+    sum_solvable(
+        content,
+        &[Operator::Add, Operator::Multiply, Operator::Concat],
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::day7::Equation;
+
+    fn solve_line(line: &str, operators: &[Operator]) -> Option<Vec<Operator>> {
+        Equation::parse(line).solve(operators)
+    }
 
     #[test]
     fn demo() {
@@ -38,6 +40,109 @@ mod tests {
         assert_eq!(11387, result);
     }
 
+    #[test]
+    fn operators() {
+        let solve = |line: &str| {
+            Equation::parse(line).solve(&[Operator::Add, Operator::Multiply, Operator::Concat])
+        };
+
+        assert_eq!(Some(vec![Operator::Multiply]), solve("190: 10 19"));
+        assert_eq!(
+            Some(vec![Operator::Add, Operator::Multiply]),
+            solve("3267: 81 40 27")
+        );
+        assert_eq!(Some(vec![Operator::Concat]), solve("156: 15 6"));
+        assert_eq!(
+            Some(vec![
+                Operator::Multiply,
+                Operator::Concat,
+                Operator::Multiply
+            ]),
+            solve("7290: 6 8 6 15")
+        );
+        assert_eq!(
+            Some(vec![Operator::Concat, Operator::Add]),
+            solve("192: 17 8 14")
+        );
+        assert_eq!(None, solve("83: 17 5"));
+        assert_eq!(None, solve("21037: 9 7 18 13"));
+
+        // the suffix has to match the whole of the last value
+        assert_eq!(None, solve("105: 1 15"));
+        assert_eq!(Some(vec![Operator::Concat]), solve("115: 1 15"));
+
+        // a zero can shrink the value, so these are solved forwards
+        assert_eq!(
+            Some(vec![Operator::Add, Operator::Multiply]),
+            solve("0: 4 5 0")
+        );
+        assert_eq!(
+            Some(vec![Operator::Add]),
+            Equation::parse("4: 4 0").solve(&[Operator::Add, Operator::Multiply])
+        );
+    }
+
+    #[test]
+    fn other_operators() {
+        let solve = |line: &str, operators: &[Operator]| Equation::parse(line).solve(operators);
+
+        assert_eq!(
+            Some(vec![Operator::Subtract]),
+            solve("5: 10 5", &[Operator::Add, Operator::Subtract])
+        );
+        assert_eq!(
+            Some(vec![Operator::Divide]),
+            solve("3: 10 3", &[Operator::Multiply, Operator::Divide])
+        );
+        assert_eq!(Some(vec![Operator::Xor]), solve("6: 5 3", &[Operator::Xor]));
+        assert_eq!(
+            Some(vec![Operator::Subtract, Operator::Multiply]),
+            solve(
+                "-6: 1 4 2",
+                &[Operator::Add, Operator::Subtract, Operator::Multiply]
+            )
+        );
+        assert_eq!(None, solve("5: 10 0", &[Operator::Divide]));
+    }
+
+    #[test]
+    fn overflow() {
+        let operators = [Operator::Add, Operator::Subtract];
+        assert_eq!(
+            Some(vec![Operator::Subtract, Operator::Add]),
+            solve_line("9223372036854775807: 9223372036854775807 1 1", &operators)
+        );
+
+        let operators = [Operator::Multiply, Operator::Divide];
+        assert_eq!(
+            Some(vec![Operator::Divide, Operator::Divide]),
+            solve_line("2305843009213693951: 9223372036854775807 2 2", &operators)
+        );
+
+        let operators = [Operator::Add, Operator::Multiply, Operator::Concat];
+        assert_eq!(
+            None,
+            solve_line("9223372036854775807: 9223372036854775807 10", &operators)
+        );
+        assert_eq!(
+            None,
+            solve_line(
+                "9223372036854775807: 1 9223372036854775807",
+                &operators[2..]
+            )
+        );
+
+        // multiplying by zero at the end doesn't make up for the overflow before it
+        assert_eq!(
+            None,
+            solve_line("0: 4611686018427387904 4 0", &[Operator::Multiply])
+        );
+        assert_eq!(
+            Some(vec![Operator::Add, Operator::Multiply]),
+            solve_line("0: 4611686018427387904 4 0", &operators)
+        );
+    }
+
     #[test]
     fn file() {
         dotenvy::dotenv().expect("should be able to load .env file!");
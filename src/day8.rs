@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub mod puzzle1;
 pub mod puzzle2;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 struct Pos {
     x: i64,
     y: i64,
@@ -25,7 +25,6 @@ enum TileType {
 
 struct Tile {
     tile_type: TileType,
-    antinodes: Vec<char>,
 }
 
 impl Tile {
@@ -36,13 +35,22 @@ impl Tile {
             TileType::Tower(c)
         };
 
-        Self {
-            tile_type,
-            antinodes: Vec::new(),
-        }
+        Self { tile_type }
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Harmonics {
+    // one antinode beyond each antenna of a pair
+    Single,
+    // grid points in line with a pair. Stepping by the gap between the antennas only finds
+    // the points a whole gap apart, reducing the gap by its gcd finds every grid point
+    // exactly in line, and the two only differ when the gap's x and y share a factor
+    Resonant { reduced: bool },
+}
+
+type Antinodes = HashMap<char, HashSet<Pos>>;
+
 #[derive(Debug)]
 struct Frequency {
     positions: Vec<Pos>,
@@ -87,94 +95,70 @@ impl Map {
         Self { grid, frequencies }
     }
 
-    fn apply_frequencies(&mut self) {
+    fn find_antinodes(&self, harmonics: Harmonics) -> Antinodes {
+        let mut antinodes = Antinodes::new();
         for (k, v) in self.frequencies.iter() {
-            let positions_len = v.positions.len();
+            let found = antinodes.entry(*k).or_default();
             for (i, pos1) in v.positions.iter().enumerate() {
-                for j in i + 1..positions_len {
-                    let pos2 = v.positions[j];
-                    let diff = pos1.difference(pos2);
-
-                    let antinode1 = Pos {
-                        x: pos1.x + diff.x,
-                        y: pos1.y + diff.y,
-                    };
-                    if let Some((tile_x, tile_y)) = self.get_tile(&antinode1) {
-                        self.grid[tile_x][tile_y].antinodes.push(*k);
-                    }
-
-                    let antinode2 = Pos {
-                        x: pos2.x - diff.x,
-                        y: pos2.y - diff.y,
-                    };
-                    if let Some((tile_x, tile_y)) = self.get_tile(&antinode2) {
-                        self.grid[tile_x][tile_y].antinodes.push(*k);
-                    }
-                }
-            }
-        }
-    }
-
-    fn apply_frequencies_with_resonance(&mut self) {
-        for (k, v) in self.frequencies.iter() {
-            let positions_len = v.positions.len();
-            for (i, pos1) in v.positions.iter().enumerate() {
-                if let Some((tower_x, tower_y)) = self.get_tile(pos1) {
-                    self.grid[tower_x][tower_y].antinodes.push(*k);
-                }
-                for j in i + 1..positions_len {
-                    let pos2 = v.positions[j];
-                    let diff = pos1.difference(pos2);
-
-                    let mut pos1_x = pos1.x;
-                    let mut pos1_y = pos1.y;
-                    let mut pos2_x = pos2.x;
-                    let mut pos2_y = pos2.y;
-
-                    loop {
-                        let mut continue_antinode_1 = true;
-                        let mut continue_antinode_2 = true;
-
-                        if continue_antinode_1 {
-                            pos1_x += diff.x;
-                            pos1_y += diff.y;
+                for pos2 in v.positions.iter().skip(i + 1) {
+                    let diff = pos1.difference(*pos2);
+                    match harmonics {
+                        Harmonics::Single => {
                             let antinode1 = Pos {
-                                x: pos1_x,
-                                y: pos1_y,
+                                x: pos1.x + diff.x,
+                                y: pos1.y + diff.y,
                             };
-                            if let Some((tile_x, tile_y)) = self.get_tile(&antinode1) {
-                                self.grid[tile_x][tile_y].antinodes.push(*k);
-                            } else {
-                                continue_antinode_1 = false;
-                            }
-                        }
-
-                        if continue_antinode_2 {
-                            pos2_x -= diff.x;
-                            pos2_y -= diff.y;
                             let antinode2 = Pos {
-                                x: pos2_x,
-                                y: pos2_y,
+                                x: pos2.x - diff.x,
+                                y: pos2.y - diff.y,
                             };
-                            if let Some((tile_x, tile_y)) = self.get_tile(&antinode2) {
-                                self.grid[tile_x][tile_y].antinodes.push(*k);
-                            } else {
-                                continue_antinode_2 = false;
-                            }
+                            found.extend(
+                                [antinode1, antinode2]
+                                    .into_iter()
+                                    .filter(|p| self.contains(p)),
+                            );
                         }
-
-                        if !continue_antinode_1 && !continue_antinode_2 {
-                            break;
+                        Harmonics::Resonant { reduced } => {
+                            let divisor = if reduced { gcd(diff.x, diff.y) } else { 1 };
+                            let step = Pos {
+                                x: diff.x / divisor,
+                                y: diff.y / divisor,
+                            };
+                            found.extend(self.walk_line(*pos1, step));
+                            found.extend(self.walk_line(
+                                *pos1,
+                                Pos {
+                                    x: -step.x,
+                                    y: -step.y,
+                                },
+                            ));
                         }
                     }
                 }
             }
         }
+
+        antinodes
+    }
+
+    // every position from start, start included, until the edge of the grid
+    fn walk_line(&self, start: Pos, step: Pos) -> Vec<Pos> {
+        let mut positions = vec![];
+        let mut pos = start;
+        while self.contains(&pos) {
+            positions.push(pos);
+            pos = Pos {
+                x: pos.x + step.x,
+                y: pos.y + step.y,
+            };
+        }
+
+        positions
     }
 
-    fn get_tile(&self, pos: &Pos) -> Option<(usize, usize)> {
+    fn contains(&self, pos: &Pos) -> bool {
         if pos.x < 0 || pos.y < 0 {
-            return None;
+            return false;
         }
 
         let max_x = self.grid.len() - 1;
@@ -185,27 +169,18 @@ impl Map {
             .len()
             - 1;
 
-        let x = pos.x as usize;
-        let y = pos.y as usize;
-        if x > max_x || y > max_y {
-            return None;
-        }
-
-        Some((x, y))
+        pos.x as usize <= max_x && pos.y as usize <= max_y
     }
 
-    fn count_tiles_with_antinodes(&self) -> i64 {
-        self.grid
-            .iter()
-            .flatten()
-            .filter(|t| !t.antinodes.is_empty())
-            .count() as i64
-    }
-
-    fn render(&self) {
-        for row in self.grid.iter() {
-            for tile in row.iter() {
-                let char = if !tile.antinodes.is_empty() {
+    fn render(&self, antinodes: &Antinodes) {
+        let all = unique_antinodes(antinodes);
+        for (x, row) in self.grid.iter().enumerate() {
+            for (y, tile) in row.iter().enumerate() {
+                let pos = Pos {
+                    x: x as i64,
+                    y: y as i64,
+                };
+                let char = if all.contains(&pos) {
                     '#'
                 } else if let TileType::Tower(c) = tile.tile_type {
                     c
@@ -218,3 +193,15 @@ impl Map {
         }
     }
 }
+
+fn unique_antinodes(antinodes: &Antinodes) -> HashSet<Pos> {
+    antinodes.values().flatten().copied().collect()
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
@@ -1,16 +1,16 @@
 use crate::read_file;
 
-use super::Map;
+use super::{unique_antinodes, Harmonics, Map};
 
 pub fn solve_day8_puzzle1() -> i64 {
     get_result(read_file("day8.txt").as_str())
 }
 
 fn get_result(content: &str) -> i64 {
-    let mut map = Map::parse(content);
-    map.apply_frequencies();
-    map.render();
-    map.count_tiles_with_antinodes()
+    let map = Map::parse(content);
+    let antinodes = map.find_antinodes(Harmonics::Single);
+    map.render(&antinodes);
+    unique_antinodes(&antinodes).len() as i64
 }
 
 #[cfg(test)]
@@ -38,6 +38,31 @@ mod test {
         assert_eq!(14, result);
     }
 
+    #[test]
+    fn per_frequency() {
+        let content = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+        let map = Map::parse(content);
+        let antinodes = map.find_antinodes(Harmonics::Single);
+
+        assert_eq!(10, antinodes[&'0'].len());
+        assert_eq!(5, antinodes[&'A'].len());
+        // some antinodes are shared between the two frequencies
+        assert_eq!(14, unique_antinodes(&antinodes).len());
+    }
+
     #[test]
     fn file() {
         dotenvy::dotenv().expect("should be able to load .env file!");
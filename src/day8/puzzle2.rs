@@ -1,21 +1,22 @@
 use crate::read_file;
 
-use super::Map;
+use super::{unique_antinodes, Harmonics, Map};
 
 pub fn solve_day8_puzzle2() -> i64 {
     get_result(read_file("day8.txt").as_str())
 }
 
 fn get_result(content: &str) -> i64 {
-    let mut map = Map::parse(content);
-    map.apply_frequencies_with_resonance();
-    map.render();
-    map.count_tiles_with_antinodes()
+    let map = Map::parse(content);
+    let antinodes = map.find_antinodes(Harmonics::Resonant { reduced: true });
+    map.render(&antinodes);
+    unique_antinodes(&antinodes).len() as i64
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::day8::Pos;
 
     #[test]
     fn demo() {
@@ -38,6 +39,81 @@ mod test {
         assert_eq!(34, result);
     }
 
+    #[test]
+    fn per_frequency() {
+        let content = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+        let map = Map::parse(content);
+        let antinodes = map.find_antinodes(Harmonics::Resonant { reduced: true });
+
+        assert_eq!(21, antinodes[&'0'].len());
+        assert_eq!(16, antinodes[&'A'].len());
+        // some antinodes are shared between the two frequencies
+        assert_eq!(34, unique_antinodes(&antinodes).len());
+    }
+
+    #[test]
+    fn reduced_steps() {
+        let content = "\
+a....
+.....
+..a..
+.....
+....b
+.....
+..b..";
+
+        let map = Map::parse(content);
+        let raw = map.find_antinodes(Harmonics::Resonant { reduced: false });
+        let reduced = map.find_antinodes(Harmonics::Resonant { reduced: true });
+
+        // a is 2 down and 2 across, so (1, 1) is exactly in line but isn't a whole gap away
+        assert_eq!(3, raw[&'a'].len());
+        assert_eq!(5, reduced[&'a'].len());
+        assert!(!raw[&'a'].contains(&Pos { x: 1, y: 1 }));
+        assert!(reduced[&'a'].contains(&Pos { x: 1, y: 1 }));
+
+        // b is 2 down and 2 back, which is also a factor of 2
+        assert_eq!(2, raw[&'b'].len());
+        assert_eq!(3, reduced[&'b'].len());
+        assert!(reduced[&'b'].contains(&Pos { x: 5, y: 3 }));
+    }
+
+    #[test]
+    fn steps_without_common_factors() {
+        let content = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+        let map = Map::parse(content);
+        let raw = map.find_antinodes(Harmonics::Resonant { reduced: false });
+        let reduced = map.find_antinodes(Harmonics::Resonant { reduced: true });
+
+        assert_eq!(raw, reduced);
+    }
+
     #[test]
     fn file() {
         dotenvy::dotenv().expect("should be able to load .env file!");
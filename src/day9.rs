@@ -1,3 +1,8 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+};
+
 pub mod puzzle1;
 pub mod puzzle2;
 
@@ -55,50 +60,284 @@ fn move_files(files: &mut [i64]) {
 }
 
 fn move_files_no_fragmentation(files: &mut [i64]) {
-    let mut i = files.len();
-    let mut fragment_len = 0;
+    let mut disk = Disk::from_blocks(files);
+    disk.compact_whole_files();
+    disk.write_blocks(files);
+}
 
-    loop {
-        i -= 1;
-        let val = files[i];
-        if val == -1 {
-            fragment_len = 0;
-            continue;
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Span {
+    pub file_id: i64,
+    pub start: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+struct Free {
+    start: usize,
+    len: usize,
+}
+
+#[derive(Debug, PartialEq)]
+struct Disk {
+    files: Vec<Span>,
+    free: Vec<Free>,
+    len: usize,
+}
+
+impl Disk {
+    fn from_blocks(blocks: &[i64]) -> Self {
+        let mut files = vec![];
+        let mut free = vec![];
+
+        let mut start = 0;
+        for (i, block) in blocks.iter().enumerate() {
+            if i + 1 < blocks.len() && blocks[i + 1] == *block {
+                continue;
+            }
+
+            let len = i + 1 - start;
+            if *block == -1 {
+                free.push(Free { start, len });
+            } else {
+                files.push(Span {
+                    file_id: *block,
+                    start,
+                    len,
+                });
+            }
+            start = i + 1;
         }
 
-        if i == 1 {
-            break;
+        Self {
+            files,
+            free,
+            len: blocks.len(),
         }
+    }
 
-        let next_val = files.get(i - 1);
+    fn write_blocks(&self, blocks: &mut [i64]) {
+        blocks.fill(-1);
+        for file in &self.files {
+            blocks[file.start..file.start + file.len].fill(file.file_id);
+        }
+    }
 
-        if next_val.is_none() {
-            break;
+    // moves every file once, highest id first, into the left most gap it fits in
+    fn compact_whole_files(&mut self) {
+        let max_len = self.free.iter().map(|f| f.len).max().unwrap_or_default();
+
+        // one min-heap of gap starts per gap length, so the left most gap that fits a file is
+        // the smallest of the heads of the buckets at least as long as the file
+        let mut buckets: Vec<BinaryHeap<Reverse<usize>>> = vec![BinaryHeap::new(); max_len + 1];
+        for free in &self.free {
+            buckets[free.len].push(Reverse(free.start));
         }
 
-        if val == *next_val.unwrap() {
-            fragment_len += 1;
-        } else {
-            let start_index = 0;
-            for j in start_index..i {
-                let slice = &files[j..=j + fragment_len];
-                if slice.iter().all(|s| *s == -1) {
-                    let mut right_index = i;
-                    for x in j..=j + fragment_len {
-                        files[x] = files[right_index];
-                        files[right_index] = -1;
-                        right_index += 1;
-                    }
-                    break;
+        self.files.sort_by_key(|f| Reverse(f.file_id));
+
+        for file in self.files.iter_mut() {
+            let best = (file.len..=max_len)
+                .filter_map(|len| buckets[len].peek().map(|Reverse(start)| (*start, len)))
+                .filter(|(start, _)| *start < file.start)
+                .min();
+
+            let Some((gap_start, gap_len)) = best else {
+                continue;
+            };
+
+            buckets[gap_len].pop();
+            let remaining = gap_len - file.len;
+            if remaining > 0 {
+                buckets[remaining].push(Reverse(gap_start + file.len));
+            }
+
+            // files only ever move left past lower ids, so the space they leave behind is
+            // never used again and doesn't need to go back into the buckets
+            file.start = gap_start;
+        }
+
+        self.files.sort_by_key(|f| f.start);
+        self.free = free_between(&self.files, self.len);
+    }
+}
+
+// files must be sorted by start
+fn free_between(files: &[Span], disk_len: usize) -> Vec<Free> {
+    let mut free = vec![];
+    let mut position = 0;
+    for file in files {
+        if file.start > position {
+            free.push(Free {
+                start: position,
+                len: file.start - position,
+            });
+        }
+        position = file.start + file.len;
+    }
+
+    if disk_len > position {
+        free.push(Free {
+            start: position,
+            len: disk_len - position,
+        });
+    }
+
+    free
+}
+
+// file ids aren't part of the disk map, so parsing the result numbers the files in the
+// order they are on disk. Runs longer than 9 are split around an empty run of the other kind
+pub fn to_disk_map(blocks: &[i64]) -> String {
+    let mut disk_map = String::new();
+    let mut is_file = true;
+
+    let mut start = 0;
+    for (i, block) in blocks.iter().enumerate() {
+        if i + 1 < blocks.len() && blocks[i + 1] == *block {
+            continue;
+        }
+
+        let run_is_file = *block != -1;
+        if run_is_file != is_file {
+            // a file straight after a file, or free space at the very start
+            disk_map.push('0');
+            is_file = !is_file;
+        }
+
+        let mut len = i + 1 - start;
+        while len > 9 {
+            disk_map.push('9');
+            disk_map.push('0');
+            len -= 9;
+        }
+        disk_map.push_str(&len.to_string());
+        is_file = !is_file;
+
+        start = i + 1;
+    }
+
+    disk_map
+}
+
+// matches the puzzle with one character a block while every id is a single digit, after
+// that every block is padded to the widest id and separated by a space
+pub fn render_layout(blocks: &[i64]) -> String {
+    let width = blocks
+        .iter()
+        .map(|b| b.max(&0).to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    let rendered: Vec<String> = blocks
+        .iter()
+        .map(|b| match b {
+            -1 => ".".repeat(width),
+            id => format!("{id:>width$}"),
+        })
+        .collect();
+
+    if width == 1 {
+        rendered.concat()
+    } else {
+        rendered.join(" ")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LayoutProblem {
+    Overlapping {
+        file_id: i64,
+        other_file_id: i64,
+        block: usize,
+    },
+    Lost {
+        file_id: i64,
+        expected: usize,
+        found: usize,
+    },
+    Duplicated {
+        file_id: i64,
+        expected: usize,
+        found: usize,
+    },
+    Resized {
+        expected: usize,
+        found: usize,
+    },
+}
+
+fn layout_problems(files: &[Span], len: usize, original: &Disk) -> Vec<LayoutProblem> {
+    let mut problems = vec![];
+
+    if len != original.len {
+        problems.push(LayoutProblem::Resized {
+            expected: original.len,
+            found: len,
+        });
+    }
+
+    // a file can reach past several of the ones starting after it, so each is checked
+    // against whichever file so far reaches the furthest
+    let mut files_by_start = files.to_vec();
+    files_by_start.sort_by_key(|f| f.start);
+    let mut furthest: Option<Span> = None;
+    for file in files_by_start {
+        match furthest {
+            Some(other) if file.start < other.start + other.len => {
+                problems.push(LayoutProblem::Overlapping {
+                    file_id: other.file_id,
+                    other_file_id: file.file_id,
+                    block: file.start,
+                });
+                if file.start + file.len > other.start + other.len {
+                    furthest = Some(file);
                 }
             }
-            fragment_len = 0;
+            _ => furthest = Some(file),
         }
+    }
 
-        if i == 0 {
-            break;
+    let mut expected: BTreeMap<i64, usize> = BTreeMap::new();
+    for file in &original.files {
+        *expected.entry(file.file_id).or_default() += file.len;
+    }
+    let mut found: BTreeMap<i64, usize> = BTreeMap::new();
+    for file in files {
+        *found.entry(file.file_id).or_default() += file.len;
+    }
+
+    let ids: BTreeSet<i64> = expected.keys().chain(found.keys()).copied().collect();
+    for file_id in ids {
+        let expected = expected.get(&file_id).copied().unwrap_or_default();
+        let found = found.get(&file_id).copied().unwrap_or_default();
+        if found < expected {
+            problems.push(LayoutProblem::Lost {
+                file_id,
+                expected,
+                found,
+            });
+        } else if found > expected {
+            problems.push(LayoutProblem::Duplicated {
+                file_id,
+                expected,
+                found,
+            });
         }
     }
+
+    problems
+}
+
+// a layout made of blocks can't have overlapping files, every block holds one file
+pub fn validate_layout(original: &[i64], layout: &[i64]) -> Vec<LayoutProblem> {
+    let disk = Disk::from_blocks(layout);
+    layout_problems(&disk.files, disk.len, &Disk::from_blocks(original))
+}
+
+// a layout where files are placed by where they start, which can put two files on a block
+pub fn validate_spans(original: &[i64], files: &[Span], len: usize) -> Vec<LayoutProblem> {
+    layout_problems(files, len, &Disk::from_blocks(original))
 }
 
 fn get_checksum(files: &[i64]) -> i64 {
@@ -112,3 +351,193 @@ fn get_checksum(files: &[i64]) -> i64 {
     }
     checksum
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spans() {
+        let blocks = parse_content("12345");
+        let disk = Disk::from_blocks(&blocks);
+
+        assert_eq!(
+            vec![
+                Span {
+                    file_id: 0,
+                    start: 0,
+                    len: 1
+                },
+                Span {
+                    file_id: 1,
+                    start: 3,
+                    len: 3
+                },
+                Span {
+                    file_id: 2,
+                    start: 10,
+                    len: 5
+                },
+            ],
+            disk.files
+        );
+        assert_eq!(
+            vec![Free { start: 1, len: 2 }, Free { start: 6, len: 4 }],
+            disk.free
+        );
+    }
+
+    #[test]
+    fn compact_whole_files() {
+        let mut disk = Disk::from_blocks(&parse_content("2333133121414131402"));
+        disk.compact_whole_files();
+
+        assert_eq!(42, disk.len);
+
+        let mut blocks = vec![0; disk.len];
+        disk.write_blocks(&mut blocks);
+        assert_eq!(2858, get_checksum(&blocks));
+        assert_eq!(disk.free, Disk::from_blocks(&blocks).free);
+    }
+
+    #[test]
+    fn gaps_right_of_a_file_are_ignored() {
+        // 0.1.2 becomes 021.. and file 1 can't move into the gap after it
+        let mut blocks = parse_content("11111");
+
+        move_files_no_fragmentation(&mut blocks);
+
+        assert_eq!(vec![0, 2, 1, -1, -1], blocks);
+    }
+
+    #[test]
+    fn layouts() {
+        let original = parse_content("2333133121414131402");
+        assert_eq!(
+            "00...111...2...333.44.5555.6666.777.888899",
+            render_layout(&original)
+        );
+
+        let mut fragmented = original.clone();
+        move_files(&mut fragmented);
+        assert_eq!(
+            "0099811188827773336446555566..............",
+            render_layout(&fragmented)
+        );
+        assert!(validate_layout(&original, &fragmented).is_empty());
+
+        let mut whole_files = original.clone();
+        move_files_no_fragmentation(&mut whole_files);
+        assert_eq!(
+            "00992111777.44.333....5555.6666.....8888..",
+            render_layout(&whole_files)
+        );
+        assert!(validate_layout(&original, &whole_files).is_empty());
+    }
+
+    #[test]
+    fn disk_map_round_trip() {
+        let disk_map = "2333133121414131402";
+        let blocks = parse_content(disk_map);
+        assert_eq!(disk_map, to_disk_map(&blocks));
+
+        let mut whole_files = blocks.clone();
+        move_files_no_fragmentation(&mut whole_files);
+        let compacted = to_disk_map(&whole_files);
+        assert_eq!("20201030312134414542", compacted);
+        assert_eq!(
+            "00112333444.55.666....7777.8888.....9999..",
+            render_layout(&parse_content(&compacted))
+        );
+
+        // long runs and free space at the start
+        let blocks = vec![-1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        let disk_map = to_disk_map(&blocks);
+        assert_eq!("0290201", disk_map);
+        let free: Vec<bool> = blocks.iter().map(|b| *b == -1).collect();
+        let parsed_free: Vec<bool> = parse_content(&disk_map).iter().map(|b| *b == -1).collect();
+        assert_eq!(free, parsed_free);
+    }
+
+    #[test]
+    fn wide_ids() {
+        let blocks = vec![0, -1, 9, 10, 10, -1];
+        assert_eq!(" 0 ..  9 10 10 ..", render_layout(&blocks));
+    }
+
+    #[test]
+    fn invalid_layouts() {
+        let original = parse_content("12345");
+
+        let mut lost = original.clone();
+        lost[3] = -1;
+        lost[14] = 0;
+        assert_eq!(
+            vec![
+                LayoutProblem::Duplicated {
+                    file_id: 0,
+                    expected: 1,
+                    found: 2
+                },
+                LayoutProblem::Lost {
+                    file_id: 1,
+                    expected: 3,
+                    found: 2
+                },
+                LayoutProblem::Lost {
+                    file_id: 2,
+                    expected: 5,
+                    found: 4
+                },
+            ],
+            validate_layout(&original, &lost)
+        );
+
+        let mut files = Disk::from_blocks(&original).files;
+        files[2].start = 4;
+        assert_eq!(
+            vec![LayoutProblem::Overlapping {
+                file_id: 1,
+                other_file_id: 2,
+                block: 4
+            }],
+            validate_spans(&original, &files, original.len())
+        );
+        assert!(validate_spans(&original, &Disk::from_blocks(&original).files, 15).is_empty());
+
+        // the first file overlaps both, even though the two after it don't touch
+        let nested = parse_content("51212");
+        let span = |file_id, start, len| Span {
+            file_id,
+            start,
+            len,
+        };
+        assert_eq!(
+            vec![
+                LayoutProblem::Overlapping {
+                    file_id: 0,
+                    other_file_id: 1,
+                    block: 1
+                },
+                LayoutProblem::Overlapping {
+                    file_id: 0,
+                    other_file_id: 2,
+                    block: 3
+                },
+            ],
+            validate_spans(
+                &nested,
+                &[span(0, 0, 5), span(1, 1, 2), span(2, 3, 2)],
+                nested.len()
+            )
+        );
+
+        assert_eq!(
+            vec![LayoutProblem::Resized {
+                expected: 15,
+                found: 14
+            }],
+            validate_layout(&original, &original[..14])[..1]
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use std::{env, fs, path::Path};
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
 
 pub mod day1;
 pub mod day10;
@@ -21,12 +25,19 @@ pub mod day7;
 pub mod day8;
 pub mod day9;
 
-fn read_file(file_name: &str) -> String {
+fn input_file_path(file_name: &str) -> PathBuf {
     let input_path = env::var("INPUT_PATH").expect("INPUT_PATH environment variable must be set");
     let input_path = Path::new(&input_path);
-    let full_path = input_path.join(file_name);
 
-    fs::read_to_string(full_path).expect("unable to read day 1 puzzle 2 file")
+    input_path.join(file_name)
+}
+
+fn read_file(file_name: &str) -> String {
+    fs::read_to_string(input_file_path(file_name)).expect("unable to read day 1 puzzle 2 file")
+}
+
+fn open_file(file_name: &str) -> File {
+    File::open(input_file_path(file_name)).expect("unable to open puzzle file")
 }
 
 fn main() {